@@ -0,0 +1,260 @@
+type ActuatorInput = {
+  kind?: string;
+  actuator?: string;
+  args?: {
+    target?: string;
+    text?: string;
+  };
+  workspace_dir?: string;
+};
+
+type ActuatorOutput = {
+  status: "completed" | "failed" | "skipped";
+  details: string;
+  sensor_output?: string;
+  structured?: Record<string, unknown>;
+};
+
+type AgentEntry = {
+  workspace_dir: string;
+  assigned_port: number;
+  agent_name?: string | null;
+  is_running: boolean;
+  mode?: string | null;
+  agent_id?: string | null;
+};
+
+type SocketMessage = {
+  type: string;
+  [key: string]: unknown;
+};
+
+const DISCOVERY_URL = "ws://127.0.0.1:10001";
+const AGENT_HOST = "127.0.0.1";
+const DEFAULT_TIMEOUT_MS = 150_000;
+
+async function readInput(): Promise<string> {
+  const decoder = new TextDecoder();
+  const chunks: Uint8Array[] = [];
+  for await (const chunk of Deno.stdin.readable) {
+    chunks.push(chunk);
+  }
+  let total = 0;
+  for (const chunk of chunks) total += chunk.length;
+  const merged = new Uint8Array(total);
+  let offset = 0;
+  for (const chunk of chunks) {
+    merged.set(chunk, offset);
+    offset += chunk.length;
+  }
+  return decoder.decode(merged);
+}
+
+function readEnv(name: string): string | undefined {
+  try {
+    return Deno.env.get(name)?.trim() || undefined;
+  } catch {
+    return undefined;
+  }
+}
+
+class SocketReader {
+  private queue: SocketMessage[] = [];
+  private waiters: ((message: SocketMessage | undefined) => void)[] = [];
+  private closed = false;
+
+  constructor(readonly socket: WebSocket) {
+    socket.onmessage = (event) => {
+      let message: SocketMessage;
+      try {
+        message = JSON.parse(String(event.data)) as SocketMessage;
+      } catch {
+        return;
+      }
+      const waiter = this.waiters.shift();
+      if (waiter) waiter(message);
+      else this.queue.push(message);
+    };
+    socket.onclose = () => {
+      this.closed = true;
+      for (const waiter of this.waiters.splice(0)) waiter(undefined);
+    };
+  }
+
+  next(): Promise<SocketMessage | undefined> {
+    const queued = this.queue.shift();
+    if (queued) return Promise.resolve(queued);
+    if (this.closed) return Promise.resolve(undefined);
+    return new Promise((resolve) => this.waiters.push(resolve));
+  }
+}
+
+async function openSocket(url: string): Promise<SocketReader> {
+  const socket = new WebSocket(url);
+  await new Promise<void>((resolve, reject) => {
+    socket.onopen = () => resolve();
+    socket.onerror = () => reject(new Error(`could not connect to ${url}`));
+  });
+  return new SocketReader(socket);
+}
+
+function send(reader: SocketReader, message: Record<string, unknown>) {
+  reader.socket.send(JSON.stringify(message));
+}
+
+async function listAgents(): Promise<AgentEntry[]> {
+  const reader = await openSocket(readEnv("HANDOFF_DISCOVERY_URL") ?? DISCOVERY_URL);
+  try {
+    send(reader, { type: "list_agents" });
+    while (true) {
+      const message = await reader.next();
+      if (!message) throw new Error("discovery closed the connection");
+      if (message.type === "agents") return message.agents as AgentEntry[];
+      if (message.type === "error") throw new Error(`discovery error: ${message.message}`);
+    }
+  } finally {
+    reader.socket.close();
+  }
+}
+
+function findTarget(agents: AgentEntry[], target: string): AgentEntry | undefined {
+  const wanted = target.toLowerCase();
+  return agents.find((agent) =>
+    agent.agent_id === target ||
+    String(agent.assigned_port) === target ||
+    (agent.agent_name ?? "").toLowerCase() === wanted
+  );
+}
+
+async function handOff(
+  target: string,
+  text: string,
+  workspaceDir: string | undefined,
+): Promise<ActuatorOutput> {
+  let agents: AgentEntry[];
+  try {
+    agents = await listAgents();
+  } catch (error) {
+    return {
+      status: "failed",
+      details: `agent lookup failed: ${error instanceof Error ? error.message : String(error)}`,
+    };
+  }
+
+  const agent = findTarget(agents, target);
+  if (!agent) {
+    return { status: "failed", details: `no agent named '${target}' is registered with discovery` };
+  }
+  if (workspaceDir && agent.workspace_dir === workspaceDir) {
+    return { status: "failed", details: `agent '${target}' is this agent; hand off to another one` };
+  }
+  if (!agent.is_running || agent.mode !== "running") {
+    return {
+      status: "failed",
+      details: `agent '${target}' is not accepting chat (${agent.is_running ? agent.mode : "stopped"})`,
+    };
+  }
+
+  const label = agent.agent_name ?? `port ${agent.assigned_port}`;
+  let reader: SocketReader;
+  try {
+    reader = await openSocket(`ws://${AGENT_HOST}:${agent.assigned_port}`);
+  } catch (error) {
+    return {
+      status: "failed",
+      details: `could not reach agent ${label}: ${error instanceof Error ? error.message : String(error)}`,
+    };
+  }
+
+  const timeoutMs = Number(readEnv("HANDOFF_TIMEOUT_MS")) || DEFAULT_TIMEOUT_MS;
+  const timer = setTimeout(() => reader.socket.close(), timeoutMs);
+  const turnId = `handoff-${Date.now()}`;
+  let response: string | undefined;
+  let failure: string | undefined;
+  try {
+    send(reader, { type: "session_start", origin: "terminal_chat" });
+    while (true) {
+      const message = await reader.next();
+      if (!message) {
+        failure = response === undefined
+          ? `agent ${label} closed the connection or timed out before responding`
+          : undefined;
+        break;
+      }
+      if (message.type === "session_started") {
+        send(reader, {
+          type: "percept_observed",
+          session_id: message.session_id,
+          domain: "chat",
+          percept: { kind: "user_text", turn_id: turnId, text },
+        });
+      } else if (message.type === "error") {
+        failure = `agent ${label} rejected the handoff: ${message.message}`;
+        break;
+      } else if (message.type === "percept_queued") {
+        failure = `agent ${label} is paused; the handoff was not run`;
+        break;
+      } else if (message.type === "effect_applied") {
+        const effect = message.effect as { kind: string; turn_id: string; text?: string; status?: string };
+        if (effect.turn_id !== turnId) continue;
+        if (effect.kind === "chat_response") {
+          response = effect.text ?? "";
+        } else if (effect.kind === "task_completion" && effect.status === "cancelled") {
+          failure = `agent ${label} cancelled the handoff turn`;
+          break;
+        } else if (effect.kind === "turn_timing") {
+          break;
+        }
+      }
+    }
+  } finally {
+    clearTimeout(timer);
+    reader.socket.close();
+  }
+
+  if (failure) {
+    return { status: "failed", details: failure };
+  }
+  return {
+    status: "completed",
+    details: `agent ${label} answered the handoff`,
+    sensor_output: [
+      "sensor plugin_command_complete:",
+      "actuator=agent_handoff",
+      `target=${JSON.stringify(label)}`,
+      `port=${agent.assigned_port}`,
+      `response=${JSON.stringify(response ?? "")}`,
+    ].join(" "),
+    structured: {
+      agent_id: agent.agent_id ?? null,
+      agent_name: agent.agent_name ?? null,
+      port: agent.assigned_port,
+      response: response ?? "",
+    },
+  };
+}
+
+const raw = await readInput();
+const payload = JSON.parse(raw) as ActuatorInput;
+
+let output: ActuatorOutput;
+
+if (payload.kind !== "actuator_execute") {
+  output = {
+    status: "skipped",
+    details: "unsupported input kind",
+  };
+} else if (payload.actuator !== "agent_handoff") {
+  output = {
+    status: "skipped",
+    details: `unsupported actuator: ${payload.actuator ?? "unknown"}`,
+  };
+} else {
+  const target = payload.args?.target?.trim() ?? "";
+  const text = payload.args?.text?.trim() ?? "";
+  output = target && text
+    ? await handOff(target, text, payload.workspace_dir)
+    : { status: "failed", details: "agent_handoff requires args.target and args.text" };
+}
+
+console.log(JSON.stringify(output));
@@ -0,0 +1,190 @@
+import { guardedFetch, privateTargetReason } from "./net-guard.ts";
+
+type ActuatorInput = {
+  kind?: string;
+  actuator?: string;
+  args?: {
+    url?: string;
+    max_bytes?: number;
+  };
+};
+
+type ActuatorOutput = {
+  status: "completed" | "failed" | "skipped";
+  details: string;
+  sensor_output?: string;
+  structured?: Record<string, unknown>;
+};
+
+const DEFAULT_MAX_BYTES = 256 * 1024;
+const DEFAULT_TIMEOUT_MS = 10_000;
+const MAX_REDIRECTS = 5;
+const BINARY_CONTENT_TYPES = [
+  "image/",
+  "audio/",
+  "video/",
+  "font/",
+  "application/octet-stream",
+  "application/pdf",
+  "application/zip",
+  "application/gzip",
+];
+
+async function readInput(): Promise<string> {
+  const decoder = new TextDecoder();
+  const chunks: Uint8Array[] = [];
+  for await (const chunk of Deno.stdin.readable) {
+    chunks.push(chunk);
+  }
+  let total = 0;
+  for (const chunk of chunks) total += chunk.length;
+  const merged = new Uint8Array(total);
+  let offset = 0;
+  for (const chunk of chunks) {
+    merged.set(chunk, offset);
+    offset += chunk.length;
+  }
+  return decoder.decode(merged);
+}
+
+function readEnv(name: string): string | undefined {
+  try {
+    return Deno.env.get(name)?.trim() || undefined;
+  } catch {
+    return undefined;
+  }
+}
+
+function readPositiveEnv(name: string, fallback: number): number {
+  const value = Number(readEnv(name));
+  return Number.isInteger(value) && value > 0 ? value : fallback;
+}
+
+async function checkTarget(url: URL): Promise<string | undefined> {
+  if (url.protocol !== "https:" && url.protocol !== "http:") {
+    return `unsupported scheme '${url.protocol}'`;
+  }
+  if (readEnv("FETCH_ALLOW_PRIVATE") === "1") {
+    return undefined;
+  }
+  const reason = await privateTargetReason(url);
+  return reason ? `${reason} (set FETCH_ALLOW_PRIVATE=1 to allow it)` : undefined;
+}
+
+async function readCapped(
+  response: Response,
+  maxBytes: number,
+): Promise<{ bytes: Uint8Array; truncated: boolean }> {
+  const merged = new Uint8Array(maxBytes);
+  let length = 0;
+  let truncated = false;
+  if (response.body) {
+    const reader = response.body.getReader();
+    while (true) {
+      const { done, value } = await reader.read();
+      if (done) break;
+      const room = maxBytes - length;
+      merged.set(value.subarray(0, room), length);
+      length += Math.min(room, value.length);
+      if (value.length > room) {
+        truncated = true;
+        await reader.cancel();
+        break;
+      }
+    }
+  }
+  return { bytes: merged.subarray(0, length), truncated };
+}
+
+async function fetchUrl(rawUrl: string, requestedMaxBytes?: number): Promise<ActuatorOutput> {
+  const byteLimit = readPositiveEnv("FETCH_MAX_BYTES", DEFAULT_MAX_BYTES);
+  const maxBytes = Number.isInteger(requestedMaxBytes) && requestedMaxBytes! > 0
+    ? Math.min(requestedMaxBytes!, byteLimit)
+    : byteLimit;
+  const signal = AbortSignal.timeout(readPositiveEnv("FETCH_TIMEOUT_MS", DEFAULT_TIMEOUT_MS));
+
+  let url: URL;
+  try {
+    url = new URL(rawUrl);
+  } catch {
+    return { status: "failed", details: `fetch refused: '${rawUrl}' is not a valid URL` };
+  }
+
+  const fetched = await guardedFetch(url, { signal }, { checkTarget, maxRedirects: MAX_REDIRECTS });
+  if ("refused" in fetched) {
+    return { status: "failed", details: `fetch refused: ${fetched.refused}` };
+  }
+  if ("error" in fetched) {
+    return { status: "failed", details: `fetch of ${fetched.url} failed: ${fetched.error}` };
+  }
+  const { response } = fetched;
+  url = fetched.url;
+
+  const contentType = response.headers.get("content-type") ?? "";
+  if (BINARY_CONTENT_TYPES.some((prefix) => contentType.toLowerCase().startsWith(prefix))) {
+    await response.body?.cancel();
+    return {
+      status: "failed",
+      details: `fetch of ${url} returned non-text content (${contentType})`,
+    };
+  }
+
+  let body: { bytes: Uint8Array; truncated: boolean };
+  try {
+    body = await readCapped(response, maxBytes);
+  } catch (error) {
+    return {
+      status: "failed",
+      details: `reading ${url} failed: ${error instanceof Error ? error.message : String(error)}`,
+    };
+  }
+  const text = new TextDecoder().decode(body.bytes);
+  const ok = response.status < 400;
+  return {
+    status: ok ? "completed" : "failed",
+    details: ok
+      ? `fetched ${body.bytes.length} bytes from ${url}${body.truncated ? " (truncated)" : ""}`
+      : `fetch of ${url} returned status ${response.status}`,
+    sensor_output: [
+      "sensor plugin_command_complete:",
+      "actuator=http_fetch",
+      `url=${url}`,
+      `status=${response.status}`,
+      `content_type=${JSON.stringify(contentType)}`,
+      `truncated=${body.truncated}`,
+      `body=${JSON.stringify(text)}`,
+    ].join(" "),
+    structured: {
+      url: url.toString(),
+      status: response.status,
+      content_type: contentType,
+      bytes: body.bytes.length,
+      truncated: body.truncated,
+      body: text,
+    },
+  };
+}
+
+const raw = await readInput();
+const payload = JSON.parse(raw) as ActuatorInput;
+
+let output: ActuatorOutput;
+
+if (payload.kind !== "actuator_execute") {
+  output = {
+    status: "skipped",
+    details: "unsupported input kind",
+  };
+} else if (payload.actuator !== "http_fetch") {
+  output = {
+    status: "skipped",
+    details: `unsupported actuator: ${payload.actuator ?? "unknown"}`,
+  };
+} else {
+  const url = payload.args?.url?.trim() ?? "";
+  output = url
+    ? await fetchUrl(url, payload.args?.max_bytes)
+    : { status: "failed", details: "http_fetch requires args.url" };
+}
+
+console.log(JSON.stringify(output));
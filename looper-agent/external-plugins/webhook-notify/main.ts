@@ -0,0 +1,147 @@
+import { guardedFetch, privateTargetReason } from "./net-guard.ts";
+
+type ActuatorInput = {
+  kind?: string;
+  actuator?: string;
+  args?: {
+    text?: string;
+  };
+};
+
+type ActuatorOutput = {
+  status: "completed" | "failed" | "skipped";
+  details: string;
+  sensor_output?: string;
+  structured?: Record<string, unknown>;
+};
+
+const DEFAULT_BODY_TEMPLATE = '{"text": {{text}}}';
+const BODY_EXCERPT_CHARS = 200;
+const DEFAULT_TIMEOUT_MS = 10_000;
+const MAX_REDIRECTS = 5;
+
+async function readInput(): Promise<string> {
+  const decoder = new TextDecoder();
+  const chunks: Uint8Array[] = [];
+  for await (const chunk of Deno.stdin.readable) {
+    chunks.push(chunk);
+  }
+  let total = 0;
+  for (const chunk of chunks) total += chunk.length;
+  const merged = new Uint8Array(total);
+  let offset = 0;
+  for (const chunk of chunks) {
+    merged.set(chunk, offset);
+    offset += chunk.length;
+  }
+  return decoder.decode(merged);
+}
+
+function readEnv(name: string): string | undefined {
+  try {
+    return Deno.env.get(name)?.trim() || undefined;
+  } catch {
+    return undefined;
+  }
+}
+
+async function checkTarget(url: URL): Promise<string | undefined> {
+  if (readEnv("WEBHOOK_ALLOW_INSECURE") === "1") {
+    return undefined;
+  }
+  if (url.protocol !== "https:") {
+    return `${url} must use https (set WEBHOOK_ALLOW_INSECURE=1 to allow http)`;
+  }
+  const reason = await privateTargetReason(url);
+  return reason ? `${reason} (set WEBHOOK_ALLOW_INSECURE=1 to allow it)` : undefined;
+}
+
+async function postWebhook(text: string): Promise<ActuatorOutput> {
+  const url = readEnv("WEBHOOK_URL");
+  if (!url) {
+    return {
+      status: "failed",
+      details: "webhook is not configured; run /plugin env webhook-notify WEBHOOK_URL=<url>",
+    };
+  }
+
+  let target: URL;
+  try {
+    target = new URL(url);
+  } catch {
+    return { status: "failed", details: "webhook refused: WEBHOOK_URL is not a valid URL" };
+  }
+
+  const template = readEnv("WEBHOOK_BODY_TEMPLATE") ?? DEFAULT_BODY_TEMPLATE;
+  const body = template.replaceAll("{{text}}", JSON.stringify(text));
+
+  const timeoutMs = Number(readEnv("WEBHOOK_TIMEOUT_MS")) || DEFAULT_TIMEOUT_MS;
+  const fetched = await guardedFetch(
+    target,
+    {
+      method: "POST",
+      headers: { "content-type": "application/json" },
+      body,
+      signal: AbortSignal.timeout(timeoutMs),
+    },
+    { checkTarget, maxRedirects: MAX_REDIRECTS },
+  );
+  if ("refused" in fetched) {
+    return { status: "failed", details: `webhook refused: ${fetched.refused}` };
+  }
+  if ("error" in fetched) {
+    return { status: "failed", details: `webhook request failed: ${fetched.error}` };
+  }
+  const { response } = fetched;
+
+  let responseBody: string;
+  try {
+    responseBody = (await response.text()).slice(0, BODY_EXCERPT_CHARS);
+  } catch (error) {
+    return {
+      status: "failed",
+      details: `webhook response failed: ${error instanceof Error ? error.message : String(error)}`,
+    };
+  }
+  const delivered = response.status < 400;
+  return {
+    status: delivered ? "completed" : "failed",
+    details: delivered
+      ? `webhook delivered with status ${response.status}`
+      : `webhook rejected with status ${response.status}`,
+    sensor_output: [
+      "sensor plugin_command_complete:",
+      "actuator=webhook_post",
+      `status=${response.status}`,
+      `body=${JSON.stringify(responseBody)}`,
+    ].join(" "),
+    structured: {
+      status: response.status,
+      body: responseBody,
+    },
+  };
+}
+
+const raw = await readInput();
+const payload = JSON.parse(raw) as ActuatorInput;
+
+let output: ActuatorOutput;
+
+if (payload.kind !== "actuator_execute") {
+  output = {
+    status: "skipped",
+    details: "unsupported input kind",
+  };
+} else if (payload.actuator !== "webhook_post") {
+  output = {
+    status: "skipped",
+    details: `unsupported actuator: ${payload.actuator ?? "unknown"}`,
+  };
+} else {
+  const text = payload.args?.text?.trim() ?? "";
+  output = text
+    ? await postWebhook(text)
+    : { status: "failed", details: "webhook_post requires args.text" };
+}
+
+console.log(JSON.stringify(output));
@@ -0,0 +1,131 @@
+const PRIVATE_NAMES = ["localhost", ".localhost", ".local", ".internal"];
+
+function parseIpv4(host: string): number[] | undefined {
+  if (!/^\d+\.\d+\.\d+\.\d+$/.test(host)) return undefined;
+  const octets = host.split(".").map((part) => Number(part));
+  return octets.every((part) => part <= 255) ? octets : undefined;
+}
+
+function parseIpv6(host: string): number[] | undefined {
+  if (!host.includes(":")) return undefined;
+  let text = host;
+  const tail: number[] = [];
+  const dotted = text.match(/:(\d+\.\d+\.\d+\.\d+)$/);
+  if (dotted) {
+    const octets = parseIpv4(dotted[1]);
+    if (!octets) return undefined;
+    tail.push((octets[0] << 8) | octets[1], (octets[2] << 8) | octets[3]);
+    text = `${text.slice(0, -dotted[1].length)}0:0`;
+  }
+
+  const halves = text.split("::");
+  if (halves.length > 2) return undefined;
+  const parse = (part: string) => part ? part.split(":").map((group) => parseInt(group, 16)) : [];
+  const head = parse(halves[0]);
+  const rest = halves.length === 2 ? parse(halves[1]) : [];
+  const missing = 8 - head.length - rest.length;
+  if (missing < 0 || (halves.length === 1 && missing !== 0)) return undefined;
+  const groups = [...head, ...Array(missing).fill(0), ...rest];
+  if (groups.some((group) => !Number.isInteger(group) || group < 0 || group > 0xffff)) {
+    return undefined;
+  }
+  if (tail.length) groups.splice(6, 2, ...tail);
+  return groups;
+}
+
+function isPrivateIpv4([a, b]: number[]): boolean {
+  return a === 0 || a === 10 || a === 127 || a >= 224 ||
+    (a === 100 && b >= 64 && b <= 127) ||
+    (a === 169 && b === 254) ||
+    (a === 172 && b >= 16 && b <= 31) ||
+    (a === 192 && b === 168) ||
+    (a === 198 && (b === 18 || b === 19));
+}
+
+function isPrivateIpv6(groups: number[]): boolean {
+  const embeddedIpv4 = [groups[6] >> 8, groups[6] & 0xff, groups[7] >> 8, groups[7] & 0xff];
+  const zeroPrefix = groups.slice(0, 5).every((group) => group === 0);
+  if (zeroPrefix && (groups[5] === 0xffff || groups[5] === 0)) {
+    return groups[5] === 0 && groups[6] === 0 ? groups[7] <= 1 : isPrivateIpv4(embeddedIpv4);
+  }
+  if (groups[0] === 0x64 && groups[1] === 0xff9b && groups.slice(2, 6).every((group) => group === 0)) {
+    return isPrivateIpv4(embeddedIpv4);
+  }
+  const first = groups[0];
+  return (first & 0xfe00) === 0xfc00 || (first & 0xffc0) === 0xfe80 ||
+    (first & 0xffc0) === 0xfec0 || (first & 0xff00) === 0xff00;
+}
+
+export function isIpLiteral(hostname: string): boolean {
+  const host = hostname.replace(/^\[|\]$/g, "");
+  return parseIpv4(host) !== undefined || parseIpv6(host) !== undefined;
+}
+
+export function isPrivateHost(hostname: string): boolean {
+  const host = hostname.replace(/^\[|\]$/g, "").replace(/\.$/, "").toLowerCase();
+  const ipv4 = parseIpv4(host);
+  if (ipv4) return isPrivateIpv4(ipv4);
+  const ipv6 = parseIpv6(host);
+  if (ipv6) return isPrivateIpv6(ipv6);
+  return PRIVATE_NAMES.some((name) => name.startsWith(".") ? host.endsWith(name) : host === name);
+}
+
+export async function privateTargetReason(url: URL): Promise<string | undefined> {
+  if (isPrivateHost(url.hostname)) {
+    return `${url.hostname} is a private or loopback host`;
+  }
+  if (isIpLiteral(url.hostname)) {
+    return undefined;
+  }
+
+  const addresses: string[] = [];
+  for (const recordType of ["A", "AAAA"] as const) {
+    try {
+      addresses.push(...await Deno.resolveDns(url.hostname, recordType));
+    } catch {
+      // A missing record type is fine; fetch reports hosts that do not resolve at all.
+    }
+  }
+  const privateAddress = addresses.find(isPrivateHost);
+  return privateAddress ? `${url.hostname} resolves to private address ${privateAddress}` : undefined;
+}
+
+export type GuardedFetch =
+  | { response: Response; url: URL }
+  | { refused: string }
+  | { error: string; url: URL };
+
+export async function guardedFetch(
+  url: URL,
+  init: RequestInit,
+  options: {
+    checkTarget: (url: URL) => Promise<string | undefined>;
+    maxRedirects: number;
+  },
+): Promise<GuardedFetch> {
+  let request = init;
+  for (let redirects = 0; redirects <= options.maxRedirects; redirects++) {
+    const refused = await options.checkTarget(url);
+    if (refused) {
+      return { refused };
+    }
+
+    let response: Response;
+    try {
+      response = await fetch(url, { ...request, redirect: "manual" });
+    } catch (error) {
+      return { error: error instanceof Error ? error.message : String(error), url };
+    }
+
+    const location = response.headers.get("location");
+    if (response.status < 300 || response.status >= 400 || !location) {
+      return { response, url };
+    }
+    await response.body?.cancel();
+    url = new URL(location, url);
+    if (response.status !== 307 && response.status !== 308) {
+      request = { ...request, method: "GET", body: undefined };
+    }
+  }
+  return { refused: `stopped after ${options.maxRedirects} redirects` };
+}
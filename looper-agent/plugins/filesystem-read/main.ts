@@ -9,6 +9,7 @@ type ActionPlan = {
   pattern?: string;
   path?: string;
   max_results?: number;
+  respect_gitignore?: boolean;
   file_path?: string;
   max_lines?: number;
 };
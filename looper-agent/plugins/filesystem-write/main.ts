@@ -0,0 +1,69 @@
+type PerceptInput = {
+  session_id: string;
+  turn_id: string;
+  text: string;
+};
+
+type ActionPlan = {
+  actuator: "filesystem_write";
+  file_path: string;
+  contents: string;
+  append: boolean;
+};
+
+type PluginPlan = {
+  actions: ActionPlan[];
+};
+
+async function readInput(): Promise<string> {
+  const decoder = new TextDecoder();
+  const chunks: Uint8Array[] = [];
+  for await (const chunk of Deno.stdin.readable) {
+    chunks.push(chunk);
+  }
+  return decoder.decode(concatChunks(chunks));
+}
+
+function concatChunks(chunks: Uint8Array[]): Uint8Array {
+  let total = 0;
+  for (const c of chunks) total += c.length;
+  const out = new Uint8Array(total);
+  let offset = 0;
+  for (const c of chunks) {
+    out.set(c, offset);
+    offset += c.length;
+  }
+  return out;
+}
+
+function parseAction(text: string): ActionPlan | null {
+  const command = text.trim().match(/^\/(write|append)\s+(\S+)\s+([\s\S]+)$/i);
+  if (!command) return null;
+
+  const filePath = cleanToken(command[2]);
+  if (!filePath) return null;
+  return {
+    actuator: "filesystem_write",
+    file_path: filePath,
+    contents: command[3],
+    append: command[1].toLowerCase() === "append",
+  };
+}
+
+function cleanToken(raw: string): string {
+  const trimmed = raw.trim();
+  if ((trimmed.startsWith("\"") && trimmed.endsWith("\"")) ||
+    (trimmed.startsWith("'") && trimmed.endsWith("'"))) {
+    return trimmed.slice(1, -1).trim();
+  }
+  return trimmed;
+}
+
+const raw = await readInput();
+const input = JSON.parse(raw) as PerceptInput;
+const action = parseAction(input.text);
+const output: PluginPlan = {
+  actions: action ? [action] : [],
+};
+
+console.log(JSON.stringify(output));
@@ -2,16 +2,19 @@ type ChatPluginPerceptInput = {
   session_id: string;
   turn_id: string;
   text: string;
+  sensor?: string;
 };
 
 type PlannedAction = {
   plugin?: string;
   actuator: string;
   args: Record<string, unknown>;
+  depends_on?: number;
+  feedback?: boolean;
 };
 
 type ChatPluginEffectOutput = {
-  mode: "stream_chat";
+  mode: "stream_chat" | "direct_actions";
   user_prompt?: string;
   system_prompt?: string;
   planned_actions?: PlannedAction[];
@@ -52,6 +55,15 @@ function buildEffects(input: ChatPluginPerceptInput): ChatPluginEffectOutput {
   const trimmed = input.text.trim();
   const plannedActions = planActions(trimmed);
 
+  if (input.sensor === "action_feedback") {
+    return {
+      mode: "stream_chat",
+      user_prompt: trimmed,
+      system_prompt: `You are Looper. Earlier actions in this conversation returned the results below. Summarize what they show and what the user should do next.\n${FORMAT_RULES}`,
+      planned_actions: [],
+    };
+  }
+
   if (!trimmed) {
     return {
       mode: "stream_chat",
@@ -76,6 +88,13 @@ function buildEffects(input: ChatPluginPerceptInput): ChatPluginEffectOutput {
     };
   }
 
+  if (plannedActions.length > 0 && isDirectFilesystemCommand(trimmed)) {
+    return {
+      mode: "direct_actions",
+      planned_actions: plannedActions,
+    };
+  }
+
   return {
     mode: "stream_chat",
     user_prompt: trimmed,
@@ -96,6 +115,18 @@ function planActions(text: string): PlannedAction[] {
   const starterPackAction = parseStarterPackCommands(text);
   if (starterPackAction.length > 0) return starterPackAction;
 
+  const notifyAction = parseNotifyCommand(text);
+  if (notifyAction.length > 0) return notifyAction;
+
+  const fetchAction = parseFetchCommand(text);
+  if (fetchAction.length > 0) return fetchAction;
+
+  const handoffAction = parseHandoffCommand(text);
+  if (handoffAction.length > 0) return handoffAction;
+
+  const writeCommand = parseWriteCommand(text);
+  if (writeCommand.length > 0) return writeCommand;
+
   const fromCommand = parseFilesystemCommand(text);
   if (fromCommand.length > 0) return fromCommand;
   return parseFilesystemRequest(text);
@@ -137,6 +168,92 @@ function parseStarterPackCommands(text: string): PlannedAction[] {
   return [];
 }
 
+function parseNotifyCommand(text: string): PlannedAction[] {
+  const notifyCommand = text.match(/^\/notify\s+([\s\S]+)$/i);
+  if (!notifyCommand) {
+    return [];
+  }
+
+  const message = notifyCommand[1].trim();
+  if (!message) {
+    return [];
+  }
+
+  return [{
+    plugin: "webhook-notify",
+    actuator: "webhook_post",
+    args: {
+      text: message,
+    },
+  }];
+}
+
+function parseFetchCommand(text: string): PlannedAction[] {
+  const fetchCommand = text.match(/^\/fetch\s+(\S+)(?:\s+(\d+))?\s*$/i);
+  if (!fetchCommand) {
+    return [];
+  }
+
+  const args: Record<string, unknown> = { url: fetchCommand[1] };
+  if (fetchCommand[2]) {
+    args.max_bytes = Number(fetchCommand[2]);
+  }
+
+  return [{
+    plugin: "http-fetch",
+    actuator: "http_fetch",
+    args,
+  }];
+}
+
+function parseHandoffCommand(text: string): PlannedAction[] {
+  const handoffCommand = text.match(/^\/handoff\s+(\S+)\s+([\s\S]+)$/i);
+  if (!handoffCommand) {
+    return [];
+  }
+
+  const message = handoffCommand[2].trim();
+  if (!message) {
+    return [];
+  }
+
+  return [{
+    plugin: "agent-handoff",
+    actuator: "agent_handoff",
+    args: {
+      target: handoffCommand[1],
+      text: message,
+    },
+  }];
+}
+
+function isDirectFilesystemCommand(text: string): boolean {
+  return /^\/(grep|glob|glop|read)\s+.+$/i.test(text) ||
+    /^\/(write|append)\s+\S+\s+[\s\S]+$/i.test(text);
+}
+
+function parseWriteCommand(text: string): PlannedAction[] {
+  const writeCommand = text.match(/^\/(write|append)\s+(\S+)\s+([\s\S]+)$/i);
+  if (!writeCommand) {
+    return [];
+  }
+
+  const filePath = cleanToken(writeCommand[2]);
+  if (!filePath) {
+    return [];
+  }
+
+  return [{
+    plugin: "filesystem-write",
+    actuator: "filesystem_write",
+    args: {
+      file_path: filePath,
+      contents: writeCommand[3],
+      append: writeCommand[1].toLowerCase() === "append",
+    },
+  }];
+}
+
 function parseFilesystemCommand(text: string): PlannedAction[] {
   const slashCommand = text.match(/^\/(grep|glob|glop|read)\s+(.+)$/i);
   if (slashCommand) {
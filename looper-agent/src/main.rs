@@ -1,21 +1,38 @@
+use std::collections::VecDeque;
 use std::env;
-use std::path::PathBuf;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, bail};
+use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
-use looper_agent::peas::PeasRuntime;
+use looper_agent::peas::{
+    AgentStartRecord, EffectStream, PeasError, PeasRuntime, PluginLimitError, TurnCancel,
+};
 use looper_agent::settings::{
-    AgentKeys, AgentSettings, PersistedAgentConfig, is_config_complete, load_persisted_config,
-    normalize_workspace_dir, persist_config,
+    AgentKeys, AgentSettings, CONFIG_BUNDLE_VERSION, ConfigBundle, PersistedAgentConfig, env_flag,
+    is_config_complete, load_persisted_config, normalize_workspace_dir, persist_config,
+    read_config_bundle, redact_secrets, write_config_bundle,
 };
 use looper_common::{
-    AGENT_HOST, AgentInfo, AgentMode, AgentSocketMessage, DEFAULT_DISCOVERY_URL, DiscoveryRequest,
-    DiscoveryResponse, PluginCommandRequest, SessionOrigin,
+    AGENT_HOST, AgentInfo, AgentMode, AgentSocketMessage, DEFAULT_DISCOVERY_URL,
+    DISCOVERY_HEARTBEAT_INTERVAL_SECS, DiscoveryRequest, DiscoveryResponse, Effect, ErrorCode,
+    Percept, PerceptBatchItem, PluginCommandRequest, SessionOrigin,
 };
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::Mutex;
-use tokio_tungstenite::{accept_async, connect_async, tungstenite::Message};
+use tokio::sync::{Mutex, Notify};
+use tokio_tungstenite::{WebSocketStream, accept_async, connect_async, tungstenite::Message};
+
+const SKIP_MODEL_VALIDATION_ENV: &str = "LOOPER_SKIP_MODEL_VALIDATION";
+const PAUSED_QUEUE_LIMIT_ENV: &str = "LOOPER_PAUSED_QUEUE_LIMIT";
+const PAUSED_QUEUE_OVERFLOW_ENV: &str = "LOOPER_PAUSED_QUEUE_OVERFLOW";
+const DEFAULT_PAUSED_QUEUE_LIMIT: usize = 50;
+const PAUSED_QUEUE_TTL_SECS_ENV: &str = "LOOPER_PAUSED_QUEUE_TTL_SECS";
+const MAX_PERCEPT_BATCH: usize = 100;
+const LOG_REQUESTS_MS_ENV: &str = "LOOPER_LOG_REQUESTS_MS";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -78,6 +95,33 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    let peas = PeasRuntime::new(registration.agent_id.clone(), agent_name.clone())?;
+    if let Some(config) = &persisted_config {
+        record_model_change(&peas, &config.settings, "startup");
+    }
+    let start_workspace = workspace_hint
+        .as_deref()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let starts = match peas.record_agent_start(&start_workspace, registration.assigned_port) {
+        Ok(starts) => starts,
+        Err(error) => {
+            eprintln!(
+                "failed to record agent start: {}",
+                redact_secrets(&format!("{error:#}"))
+            );
+            AgentStartRecord {
+                first_started_at: unix_millis(),
+                restart_count: 0,
+            }
+        }
+    };
+    println!(
+        "agent start #{} on port {}",
+        starts.restart_count + 1,
+        registration.assigned_port
+    );
+
     let runtime = Arc::new(Mutex::new(AgentRuntime {
         agent_id: registration.agent_id.clone(),
         assigned_port: registration.assigned_port,
@@ -85,22 +129,46 @@ async fn main() -> anyhow::Result<()> {
         persisted: persisted_config,
         workspace_hint,
         agent_name,
-        peas: PeasRuntime::new(registration.agent_id.clone())?,
+        peas,
+        models_ok: None,
+        started_at: Instant::now(),
+        starts,
+        resumed: Arc::new(Notify::new()),
     }));
 
     let server_handle = tokio::spawn(run_agent_server(runtime, discovery_url.clone()));
 
-    while let Some(message) = reader.next().await {
+    let mut heartbeat =
+        tokio::time::interval(Duration::from_secs(DISCOVERY_HEARTBEAT_INTERVAL_SECS));
+    loop {
+        let message = tokio::select! {
+            _ = heartbeat.tick() => {
+                if let Err(error) = writer.send(Message::Ping(Vec::new())).await {
+                    eprintln!(
+                        "discovery heartbeat failed: {}",
+                        redact_secrets(&error.to_string())
+                    );
+                    break;
+                }
+                continue;
+            }
+            message = reader.next() => message,
+        };
+
         match message {
-            Ok(Message::Close(_)) => {
+            Some(Ok(Message::Close(_))) => {
                 println!("discovery connection closed");
                 break;
             }
-            Ok(_) => {}
-            Err(error) => {
-                eprintln!("discovery connection error: {error}");
+            Some(Ok(_)) => {}
+            Some(Err(error)) => {
+                eprintln!(
+                    "discovery connection error: {}",
+                    redact_secrets(&error.to_string())
+                );
                 break;
             }
+            None => break,
         }
     }
 
@@ -154,6 +222,38 @@ struct RegistrationInfo {
     active_agents: Vec<AgentInfo>,
 }
 
+#[derive(Debug)]
+enum ConfigRequestError {
+    NotConfigured,
+    Invalid(String),
+    NotFound(String),
+}
+
+impl fmt::Display for ConfigRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConfigured => write!(f, "agent is missing persisted configuration"),
+            Self::Invalid(message) | Self::NotFound(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigRequestError {}
+
+impl ConfigRequestError {
+    fn code_for(error: &anyhow::Error) -> ErrorCode {
+        if let Some(peas_error) = error.downcast_ref::<PeasError>() {
+            return peas_error.code();
+        }
+        match error.downcast_ref::<Self>() {
+            Some(Self::NotConfigured) => ErrorCode::NotConfigured,
+            Some(Self::Invalid(_)) => ErrorCode::InvalidRequest,
+            Some(Self::NotFound(_)) => ErrorCode::NotFound,
+            None => ErrorCode::Internal,
+        }
+    }
+}
+
 struct CliArgs {
     port: Option<u16>,
     workspace_dir: Option<String>,
@@ -167,6 +267,10 @@ struct AgentRuntime {
     workspace_hint: Option<PathBuf>,
     agent_name: Option<String>,
     peas: PeasRuntime,
+    models_ok: Option<bool>,
+    started_at: Instant,
+    starts: AgentStartRecord,
+    resumed: Arc<Notify>,
 }
 
 fn parse_args() -> anyhow::Result<CliArgs> {
@@ -225,7 +329,10 @@ async fn run_agent_server(
 
         tokio::spawn(async move {
             if let Err(error) = handle_user_socket(stream, runtime, discovery_url).await {
-                eprintln!("user websocket handler failed: {error:#}");
+                eprintln!(
+                    "user websocket handler failed: {}",
+                    redact_secrets(&format!("{error:#}"))
+                );
             }
         });
     }
@@ -239,13 +346,18 @@ async fn handle_user_socket(
     let ws_stream = accept_async(stream)
         .await
         .context("agent websocket handshake failed")?;
-    let (mut writer, mut reader) = ws_stream.split();
+    let (mut writer, reader) = ws_stream.split();
+    let mut reader = UserSocketReader {
+        stream: reader,
+        deferred: VecDeque::new(),
+    };
 
     let runtime_guard = runtime.lock().await;
     let hello = AgentSocketMessage::AgentHello {
         agent_id: runtime_guard.agent_id.clone(),
         mode: runtime_guard.mode,
     };
+    let resumed = Arc::clone(&runtime_guard.resumed);
     drop(runtime_guard);
 
     writer
@@ -254,10 +366,34 @@ async fn handle_user_socket(
         .context("failed to send agent hello message")?;
 
     let mut active_session_id: Option<String> = None;
+    let mut queued_percepts = PausedQueue::from_env();
+    let log_threshold = env::var(LOG_REQUESTS_MS_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_millis);
+
+    loop {
+        let notified = resumed.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        if !queued_percepts.percepts.is_empty() {
+            resume_paused_queue(&mut writer, &mut reader, &runtime, &mut queued_percepts).await?;
+        }
+        let message = tokio::select! {
+            message = reader.next() => message,
+            _ = &mut notified => continue,
+        };
+        let Some(message) = message else {
+            break;
+        };
 
-    while let Some(message) = reader.next().await {
         match message {
             Ok(Message::Text(text)) => {
+                let _request_log = log_threshold.map(|threshold| RequestLog {
+                    kind: request_kind(&text),
+                    threshold,
+                    started: Instant::now(),
+                });
                 let parsed = serde_json::from_str::<AgentSocketMessage>(&text);
 
                 let parsed = match parsed {
@@ -265,6 +401,7 @@ async fn handle_user_socket(
                     Err(error) => {
                         let response = AgentSocketMessage::Error {
                             message: format!("invalid agent socket payload: {error}"),
+                            code: ErrorCode::InvalidRequest,
                         };
                         writer
                             .send(Message::Text(serde_json::to_string(&response)?.into()))
@@ -303,7 +440,8 @@ async fn handle_user_socket(
                         }
                         Err(error) => {
                             let response = AgentSocketMessage::Error {
-                                message: error.to_string(),
+                                message: redact_secrets(&error.to_string()),
+                                code: ErrorCode::InvalidRequest,
                             };
                             writer
                                 .send(Message::Text(serde_json::to_string(&response)?.into()))
@@ -315,6 +453,7 @@ async fn handle_user_socket(
                         if origin != SessionOrigin::TerminalChat {
                             let response = AgentSocketMessage::Error {
                                 message: "only terminal_chat origin is supported".to_string(),
+                                code: ErrorCode::InvalidRequest,
                             };
                             writer
                                 .send(Message::Text(serde_json::to_string(&response)?.into()))
@@ -324,10 +463,11 @@ async fn handle_user_socket(
                         }
 
                         let runtime_guard = runtime.lock().await;
-                        if runtime_guard.mode != AgentMode::Running {
+                        if runtime_guard.mode == AgentMode::Setup {
                             drop(runtime_guard);
                             let response = AgentSocketMessage::Error {
                                 message: "agent is in setup mode".to_string(),
+                                code: ErrorCode::SetupRequired,
                             };
                             writer
                                 .send(Message::Text(serde_json::to_string(&response)?.into()))
@@ -345,6 +485,7 @@ async fn handle_user_socket(
                                 let response = AgentSocketMessage::Error {
                                     message: "agent is missing provider/model configuration"
                                         .to_string(),
+                                    code: ErrorCode::NotConfigured,
                                 };
                                 writer
                                     .send(Message::Text(serde_json::to_string(&response)?.into()))
@@ -375,12 +516,14 @@ async fn handle_user_socket(
                         session_id,
                         domain,
                         percept,
+                        priority,
                     } => {
                         let runtime_guard = runtime.lock().await;
-                        if runtime_guard.mode != AgentMode::Running {
+                        if runtime_guard.mode == AgentMode::Setup {
                             drop(runtime_guard);
                             let response = AgentSocketMessage::Error {
                                 message: "agent is in setup mode".to_string(),
+                                code: ErrorCode::SetupRequired,
                             };
                             writer
                                 .send(Message::Text(serde_json::to_string(&response)?.into()))
@@ -388,6 +531,7 @@ async fn handle_user_socket(
                                 .context("failed to send setup mode warning")?;
                             continue;
                         }
+                        let paused = runtime_guard.mode == AgentMode::Paused;
                         let persisted = runtime_guard.persisted.clone();
                         let peas = runtime_guard.peas.clone();
                         drop(runtime_guard);
@@ -396,6 +540,7 @@ async fn handle_user_socket(
                             let response = AgentSocketMessage::Error {
                                 message: "percept session does not match active terminal session"
                                     .to_string(),
+                                code: ErrorCode::SessionMismatch,
                             };
                             writer
                                 .send(Message::Text(serde_json::to_string(&response)?.into()))
@@ -404,9 +549,38 @@ async fn handle_user_socket(
                             continue;
                         }
 
+                        if let Some(response) = client_percept_rejection(&percept) {
+                            writer
+                                .send(Message::Text(serde_json::to_string(&response)?))
+                                .await
+                                .context("failed to send percept rejection")?;
+                            continue;
+                        }
+
+                        if paused {
+                            queued_percepts.push(QueuedPercept {
+                                session_id: session_id.clone(),
+                                domain,
+                                percept,
+                                priority,
+                                queued_at: Instant::now(),
+                            });
+                            let response = AgentSocketMessage::PerceptQueued {
+                                session_id,
+                                queued: queued_percepts.percepts.len(),
+                                dropped: queued_percepts.dropped,
+                            };
+                            writer
+                                .send(Message::Text(serde_json::to_string(&response)?))
+                                .await
+                                .context("failed to send percept queued notice")?;
+                            continue;
+                        }
+
                         let Some(persisted) = persisted else {
                             let response = AgentSocketMessage::Error {
                                 message: "agent is missing persisted provider settings".to_string(),
+                                code: ErrorCode::NotConfigured,
                             };
                             writer
                                 .send(Message::Text(serde_json::to_string(&response)?.into()))
@@ -415,35 +589,289 @@ async fn handle_user_socket(
                             continue;
                         };
 
-                        let mut effects = peas
-                            .stream_percept_effects(
-                                &session_id,
-                                &domain,
-                                percept,
-                                &persisted.settings.workspace_dir,
-                                &persisted.settings.provider,
-                                &persisted.settings.model,
-                                &persisted.keys,
-                            )
-                            .await?;
+                        apply_percept(
+                            &mut writer,
+                            &mut reader,
+                            &peas,
+                            &persisted,
+                            session_id,
+                            domain,
+                            percept,
+                        )
+                        .await?;
+                    }
+                    AgentSocketMessage::PerceptBatch {
+                        session_id,
+                        domain,
+                        percepts,
+                    } => {
+                        let runtime_guard = runtime.lock().await;
+                        let mode = runtime_guard.mode;
+                        let persisted = runtime_guard.persisted.clone();
+                        let peas = runtime_guard.peas.clone();
+                        drop(runtime_guard);
 
-                        while let Some(effect) = effects.next().await {
-                            let effect = effect?;
-                            let response = AgentSocketMessage::EffectApplied {
-                                session_id: session_id.clone(),
-                                domain: domain.clone(),
-                                effect: effect.clone(),
+                        let rejection = if mode == AgentMode::Setup {
+                            Some((ErrorCode::SetupRequired, "agent is in setup mode"))
+                        } else if active_session_id.as_deref() != Some(session_id.as_str()) {
+                            Some((
+                                ErrorCode::SessionMismatch,
+                                "percept session does not match active terminal session",
+                            ))
+                        } else if domain != "chat" {
+                            Some((
+                                ErrorCode::InvalidRequest,
+                                "percept batches only support the chat domain",
+                            ))
+                        } else if persisted.is_none() {
+                            Some((
+                                ErrorCode::NotConfigured,
+                                "agent is missing persisted provider settings",
+                            ))
+                        } else {
+                            None
+                        };
+                        if let Some((code, message)) = rejection {
+                            let response = AgentSocketMessage::Error {
+                                message: message.to_string(),
+                                code,
                             };
                             writer
-                                .send(Message::Text(serde_json::to_string(&response)?.into()))
+                                .send(Message::Text(serde_json::to_string(&response)?))
                                 .await
-                                .context("failed to send effect response")?;
-                            peas.record_effect(&session_id, &effect)?;
+                                .context("failed to send percept batch rejection")?;
+                            continue;
+                        }
+
+                        let paused = mode == AgentMode::Paused;
+                        let mut results = Vec::new();
+                        let mut accepted = Vec::new();
+                        for (index, percept) in percepts.into_iter().enumerate() {
+                            let problem = batch_percept_problem(index, &percept);
+                            results.push(PerceptBatchItem {
+                                index,
+                                accepted: problem.is_none(),
+                                message: problem,
+                            });
+                            if results.last().is_some_and(|item| item.accepted) {
+                                accepted.push(percept);
+                            }
+                        }
+
+                        let response = AgentSocketMessage::PerceptBatchResult {
+                            session_id: session_id.clone(),
+                            queued: paused,
+                            results,
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send percept batch result")?;
+
+                        if paused {
+                            for percept in accepted {
+                                queued_percepts.push(QueuedPercept {
+                                    session_id: session_id.clone(),
+                                    domain: domain.clone(),
+                                    percept,
+                                    priority: 0,
+                                    queued_at: Instant::now(),
+                                });
+                            }
+                            continue;
+                        }
+
+                        let Some(persisted) = persisted else {
+                            continue;
+                        };
+                        for percept in accepted {
+                            apply_percept(
+                                &mut writer,
+                                &mut reader,
+                                &peas,
+                                &persisted,
+                                session_id.clone(),
+                                domain.clone(),
+                                percept,
+                            )
+                            .await?;
+                        }
+                    }
+                    AgentSocketMessage::CheckReadiness => {
+                        let runtime_guard = runtime.lock().await;
+                        let mode = runtime_guard.mode;
+                        let configured = runtime_guard
+                            .persisted
+                            .as_ref()
+                            .is_some_and(is_config_complete);
+                        let models_ok = runtime_guard.models_ok;
+                        let peas = runtime_guard.peas.clone();
+                        let uptime_secs = runtime_guard.started_at.elapsed().as_secs();
+                        let starts = runtime_guard.starts;
+                        drop(runtime_guard);
+
+                        let store = peas.check_store();
+                        let store_ok = store.is_ok();
+                        let message = match store {
+                            Err(error) => {
+                                redact_secrets(&format!("chat store unavailable: {error:#}"))
+                            }
+                            Ok(()) if !configured || mode == AgentMode::Setup => {
+                                "agent is in setup mode".to_string()
+                            }
+                            Ok(()) if models_ok == Some(false) => {
+                                "last model check failed; run /model check".to_string()
+                            }
+                            Ok(()) if mode == AgentMode::Paused => "agent is paused".to_string(),
+                            Ok(()) => "ready".to_string(),
+                        };
+                        let response = AgentSocketMessage::Readiness {
+                            mode,
+                            configured,
+                            store_ok,
+                            models_ok,
+                            message,
+                            uptime_secs,
+                            restart_count: starts.restart_count,
+                            first_started_at: starts.first_started_at,
+                            stalled_turns: peas.stalled_turns(),
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send readiness")?;
+                    }
+                    AgentSocketMessage::CancelTurn { .. } => {
+                        let response = AgentSocketMessage::Error {
+                            message: "no chat turn is in progress to cancel".to_string(),
+                            code: ErrorCode::InvalidState,
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send cancel result")?;
+                    }
+                    AgentSocketMessage::PauseAgent => {
+                        let mut runtime_guard = runtime.lock().await;
+                        let response = match runtime_guard.mode {
+                            AgentMode::Setup => AgentSocketMessage::Error {
+                                message: "agent is in setup mode".to_string(),
+                                code: ErrorCode::SetupRequired,
+                            },
+                            AgentMode::Running | AgentMode::Paused => {
+                                runtime_guard.mode = AgentMode::Paused;
+                                AgentSocketMessage::ModeChanged {
+                                    mode: AgentMode::Paused,
+                                    expired: 0,
+                                }
+                            }
+                        };
+                        drop(runtime_guard);
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send pause result")?;
+                    }
+                    AgentSocketMessage::ResumeAgent => {
+                        let mut runtime_guard = runtime.lock().await;
+                        let response = match runtime_guard.mode {
+                            AgentMode::Setup => AgentSocketMessage::Error {
+                                message: "agent is in setup mode".to_string(),
+                                code: ErrorCode::SetupRequired,
+                            },
+                            AgentMode::Running | AgentMode::Paused => {
+                                runtime_guard.mode = AgentMode::Running;
+                                runtime_guard.resumed.notify_waiters();
+                                AgentSocketMessage::ModeChanged {
+                                    mode: AgentMode::Running,
+                                    expired: queued_percepts.expire(),
+                                }
+                            }
+                        };
+                        let persisted = runtime_guard.persisted.clone();
+                        let peas = runtime_guard.peas.clone();
+                        drop(runtime_guard);
+                        let resumed = matches!(response, AgentSocketMessage::ModeChanged { .. });
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send resume result")?;
+
+                        if resumed && let Some(persisted) = persisted {
+                            run_paused_queue(
+                                &mut writer,
+                                &mut reader,
+                                &peas,
+                                &persisted,
+                                &mut queued_percepts,
+                            )
+                            .await?;
+                        }
+                    }
+                    AgentSocketMessage::StepAgent => {
+                        let runtime_guard = runtime.lock().await;
+                        let paused = runtime_guard.mode == AgentMode::Paused;
+                        let persisted = runtime_guard.persisted.clone();
+                        let peas = runtime_guard.peas.clone();
+                        drop(runtime_guard);
+
+                        let expired = if paused { queued_percepts.expire() } else { 0 };
+                        let next = if paused {
+                            queued_percepts.percepts.pop_front()
+                        } else {
+                            None
+                        };
+                        let response = match &next {
+                            _ if !paused => AgentSocketMessage::Error {
+                                message: "agent is not paused; /step only runs while paused"
+                                    .to_string(),
+                                code: ErrorCode::InvalidState,
+                            },
+                            None if expired > 0 => AgentSocketMessage::Error {
+                                message: format!(
+                                    "no queued messages to step; {expired} expired after {}s",
+                                    queued_percepts.ttl.unwrap_or_default().as_secs()
+                                ),
+                                code: ErrorCode::InvalidState,
+                            },
+                            None => AgentSocketMessage::Error {
+                                message: "no queued messages to step".to_string(),
+                                code: ErrorCode::InvalidState,
+                            },
+                            Some(queued) => AgentSocketMessage::AgentStepped {
+                                session_id: queued.session_id.clone(),
+                                remaining: queued_percepts.percepts.len(),
+                                expired,
+                            },
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send step result")?;
+
+                        if let Some(QueuedPercept {
+                            session_id,
+                            domain,
+                            percept,
+                            ..
+                        }) = next
+                            && let Some(persisted) = persisted
+                        {
+                            apply_percept(
+                                &mut writer,
+                                &mut reader,
+                                &peas,
+                                &persisted,
+                                session_id,
+                                domain,
+                                percept,
+                            )
+                            .await?;
                         }
                     }
                     AgentSocketMessage::PluginCommand { command } => {
                         let runtime_guard = runtime.lock().await;
-                        if runtime_guard.mode != AgentMode::Running {
+                        if runtime_guard.mode == AgentMode::Setup {
                             drop(runtime_guard);
                             let response = AgentSocketMessage::PluginCommandResult {
                                 command,
@@ -472,6 +900,18 @@ async fn handle_user_socket(
                                 };
                                 peas.install_workspace_plugin(&workspace_dir, source)
                             }
+                            PluginCommandRequest::Validate { source } => {
+                                let Some(workspace_dir) = workspace_dir.clone() else {
+                                    bail!("agent is missing persisted workspace configuration");
+                                };
+                                let peas = peas.clone();
+                                let source = source.clone();
+                                tokio::task::spawn_blocking(move || {
+                                    peas.validate_plugin_source(&workspace_dir, &source)
+                                })
+                                .await
+                                .context("plugin validation task failed")?
+                            }
                             PluginCommandRequest::Remove { plugin_name } => {
                                 let Some(workspace_dir) = workspace_dir.as_deref() else {
                                     bail!("agent is missing persisted workspace configuration");
@@ -494,19 +934,70 @@ async fn handle_user_socket(
                                     false,
                                 )
                             }
+                            PluginCommandRequest::SetEnv {
+                                plugin_name,
+                                key,
+                                value,
+                            } => {
+                                let Some(workspace_dir) = workspace_dir.as_deref() else {
+                                    bail!("agent is missing persisted workspace configuration");
+                                };
+                                peas.set_workspace_plugin_env(
+                                    workspace_dir,
+                                    plugin_name,
+                                    key,
+                                    value.as_deref(),
+                                )
+                            }
                             PluginCommandRequest::List => {
                                 let Some(workspace_dir) = workspace_dir.as_deref() else {
                                     bail!("agent is missing persisted workspace configuration");
                                 };
                                 peas.list_workspace_plugins(&workspace_dir)
                             }
+                            PluginCommandRequest::Reload => {
+                                let Some(workspace_dir) = workspace_dir.as_deref() else {
+                                    bail!("agent is missing persisted workspace configuration");
+                                };
+                                peas.reload_plugins(workspace_dir)
+                            }
+                            PluginCommandRequest::Status => {
+                                let Some(workspace_dir) = workspace_dir.as_deref() else {
+                                    bail!("agent is missing persisted workspace configuration");
+                                };
+                                match peas.workspace_plugin_statuses(workspace_dir) {
+                                    Ok(plugins) => {
+                                        let summary = format!("{} plugins", plugins.len());
+                                        let response =
+                                            AgentSocketMessage::PluginStatuses { plugins };
+                                        writer
+                                            .send(Message::Text(serde_json::to_string(&response)?))
+                                            .await
+                                            .context("failed to send plugin statuses")?;
+                                        Ok(summary)
+                                    }
+                                    Err(error) => Err(error),
+                                }
+                            }
                         };
 
                         let (success, message) = match result {
                             Ok(message) => (true, message),
-                            Err(error) => (false, error.to_string()),
+                            Err(error) => (false, redact_secrets(&error.to_string())),
                         };
 
+                        let command = match command {
+                            PluginCommandRequest::SetEnv {
+                                plugin_name,
+                                key,
+                                value: Some(_),
+                            } => PluginCommandRequest::SetEnv {
+                                plugin_name,
+                                key,
+                                value: Some("[redacted]".to_string()),
+                            },
+                            command => command,
+                        };
                         let response = AgentSocketMessage::PluginCommandResult {
                             command,
                             success,
@@ -517,6 +1008,203 @@ async fn handle_user_socket(
                             .await
                             .context("failed to send plugin command result")?;
                     }
+                    AgentSocketMessage::ExportEvents {
+                        session_id,
+                        format,
+                        path,
+                    } => {
+                        let runtime_guard = runtime.lock().await;
+                        let peas = runtime_guard.peas.clone();
+                        let workspace_dir = runtime_guard
+                            .persisted
+                            .as_ref()
+                            .map(|config| config.settings.workspace_dir.clone());
+                        drop(runtime_guard);
+
+                        let mut export_path = PathBuf::from(path.trim());
+                        if export_path.is_relative()
+                            && let Some(workspace_dir) = workspace_dir
+                        {
+                            export_path = PathBuf::from(workspace_dir).join(export_path);
+                        }
+
+                        let response =
+                            match peas.export_events(session_id.as_deref(), format, &export_path) {
+                                Ok(count) => AgentSocketMessage::EventsExported {
+                                    path: export_path.to_string_lossy().to_string(),
+                                    format,
+                                    count,
+                                },
+                                Err(error) => AgentSocketMessage::Error {
+                                    message: redact_secrets(&format!(
+                                        "event export failed: {error:#}"
+                                    )),
+                                    code: ErrorCode::Internal,
+                                },
+                            };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send event export result")?;
+                    }
+                    AgentSocketMessage::SearchChat { query, limit } => {
+                        let runtime_guard = runtime.lock().await;
+                        let peas = runtime_guard.peas.clone();
+                        drop(runtime_guard);
+
+                        let response = match peas.search_chat_messages(&query, limit) {
+                            Ok(hits) => AgentSocketMessage::ChatSearchResults { query, hits },
+                            Err(error) => AgentSocketMessage::Error {
+                                message: redact_secrets(&format!("chat search failed: {error:#}")),
+                                code: ErrorCode::Internal,
+                            },
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send chat search results")?;
+                    }
+                    AgentSocketMessage::QueryModelHistory { limit } => {
+                        let runtime_guard = runtime.lock().await;
+                        let peas = runtime_guard.peas.clone();
+                        let workspace_dir = runtime_guard
+                            .persisted
+                            .as_ref()
+                            .map(|config| config.settings.workspace_dir.clone());
+                        drop(runtime_guard);
+
+                        let result = match workspace_dir {
+                            Some(workspace_dir) => peas.model_history(&workspace_dir, limit),
+                            None => Err(anyhow::anyhow!(
+                                "agent is missing persisted workspace configuration"
+                            )),
+                        };
+                        let response = match result {
+                            Ok(entries) => AgentSocketMessage::ModelHistory { entries },
+                            Err(error) => AgentSocketMessage::Error {
+                                message: redact_secrets(&format!(
+                                    "model history query failed: {error:#}"
+                                )),
+                                code: ErrorCode::Internal,
+                            },
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send model history")?;
+                    }
+                    AgentSocketMessage::QueryAudit {
+                        actuator,
+                        since,
+                        until,
+                        limit,
+                    } => {
+                        let runtime_guard = runtime.lock().await;
+                        let peas = runtime_guard.peas.clone();
+                        drop(runtime_guard);
+
+                        let response =
+                            match peas.query_audit(actuator.as_deref(), since, until, limit) {
+                                Ok(entries) => AgentSocketMessage::AuditEntries { entries },
+                                Err(error) => AgentSocketMessage::Error {
+                                    message: redact_secrets(&format!(
+                                        "audit query failed: {error:#}"
+                                    )),
+                                    code: ErrorCode::Internal,
+                                },
+                            };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send audit entries")?;
+                    }
+                    AgentSocketMessage::RemoveProviderKey { provider } => {
+                        let response = match remove_provider_key(&runtime, &provider).await {
+                            Ok(mode) => AgentSocketMessage::ProviderKeyRemoved { provider, mode },
+                            Err(error) => AgentSocketMessage::Error {
+                                message: redact_secrets(&format!("{error:#}")),
+                                code: ConfigRequestError::code_for(&error),
+                            },
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send provider key removal result")?;
+                    }
+                    AgentSocketMessage::ExportConfig { path, include_keys } => {
+                        let response = match export_config(&runtime, &path, include_keys).await {
+                            Ok(path) => AgentSocketMessage::ConfigExported {
+                                path: path.to_string_lossy().to_string(),
+                                keys_included: include_keys,
+                            },
+                            Err(error) => AgentSocketMessage::Error {
+                                message: redact_secrets(&format!(
+                                    "config export failed: {error:#}"
+                                )),
+                                code: ErrorCode::Internal,
+                            },
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send config export result")?;
+                    }
+                    AgentSocketMessage::ImportConfig { path, include_keys } => {
+                        let response = match import_config(&runtime, &path, include_keys).await {
+                            Ok((path, mode, keys_imported)) => AgentSocketMessage::ConfigImported {
+                                path: path.to_string_lossy().to_string(),
+                                mode,
+                                keys_imported,
+                            },
+                            Err(error) => AgentSocketMessage::Error {
+                                message: redact_secrets(&format!(
+                                    "config import failed: {error:#}"
+                                )),
+                                code: ConfigRequestError::code_for(&error),
+                            },
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send config import result")?;
+                    }
+                    AgentSocketMessage::CheckModel => {
+                        let runtime_guard = runtime.lock().await;
+                        let peas = runtime_guard.peas.clone();
+                        let persisted = runtime_guard.persisted.clone();
+                        drop(runtime_guard);
+
+                        let response = match persisted {
+                            Some(persisted) => {
+                                let result = peas
+                                    .check_model(
+                                        &persisted.settings.provider,
+                                        &persisted.settings.model,
+                                        &persisted.keys,
+                                    )
+                                    .await;
+                                runtime.lock().await.models_ok = Some(result.is_ok());
+                                AgentSocketMessage::ModelHealth {
+                                    provider: persisted.settings.provider,
+                                    model: persisted.settings.model,
+                                    ok: result.is_ok(),
+                                    message: match result {
+                                        Ok(()) => "model responded".to_string(),
+                                        Err(error) => redact_secrets(&format!("{error:#}")),
+                                    },
+                                }
+                            }
+                            None => AgentSocketMessage::Error {
+                                message: "agent is missing provider/model configuration"
+                                    .to_string(),
+                                code: ErrorCode::NotConfigured,
+                            },
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send model health")?;
+                    }
                     AgentSocketMessage::SessionEnd { session_id } => {
                         let runtime_guard = runtime.lock().await;
                         let peas = runtime_guard.peas.clone();
@@ -532,6 +1220,20 @@ async fn handle_user_socket(
                     | AgentSocketMessage::Error { .. }
                     | AgentSocketMessage::SessionStarted { .. }
                     | AgentSocketMessage::PluginCommandResult { .. }
+                    | AgentSocketMessage::PluginStatuses { .. }
+                    | AgentSocketMessage::EventsExported { .. }
+                    | AgentSocketMessage::ChatSearchResults { .. }
+                    | AgentSocketMessage::AuditEntries { .. }
+                    | AgentSocketMessage::ModelHistory { .. }
+                    | AgentSocketMessage::PerceptBatchResult { .. }
+                    | AgentSocketMessage::ModelHealth { .. }
+                    | AgentSocketMessage::ProviderKeyRemoved { .. }
+                    | AgentSocketMessage::ConfigExported { .. }
+                    | AgentSocketMessage::ConfigImported { .. }
+                    | AgentSocketMessage::ModeChanged { .. }
+                    | AgentSocketMessage::AgentStepped { .. }
+                    | AgentSocketMessage::Readiness { .. }
+                    | AgentSocketMessage::PerceptQueued { .. }
                     | AgentSocketMessage::EffectApplied { .. } => {}
                 }
             }
@@ -551,6 +1253,360 @@ async fn handle_user_socket(
     Ok(())
 }
 
+async fn apply_percept(
+    writer: &mut SplitSink<WebSocketStream<TcpStream>, Message>,
+    reader: &mut UserSocketReader,
+    peas: &PeasRuntime,
+    persisted: &PersistedAgentConfig,
+    session_id: String,
+    domain: String,
+    percept: Percept,
+) -> anyhow::Result<()> {
+    peas.take_pending_feedback(&session_id);
+    let mut next = Some(percept);
+    while let Some(percept) = next.take() {
+        if !apply_single_percept(
+            writer,
+            reader,
+            peas,
+            persisted,
+            &session_id,
+            &domain,
+            percept,
+        )
+        .await?
+        {
+            peas.take_pending_feedback(&session_id);
+            break;
+        }
+        next = peas.take_pending_feedback(&session_id);
+    }
+    Ok(())
+}
+
+async fn apply_single_percept(
+    writer: &mut SplitSink<WebSocketStream<TcpStream>, Message>,
+    reader: &mut UserSocketReader,
+    peas: &PeasRuntime,
+    persisted: &PersistedAgentConfig,
+    session_id: &str,
+    domain: &str,
+    percept: Percept,
+) -> anyhow::Result<bool> {
+    let turn_id = match &percept {
+        Percept::UserText { turn_id, .. } | Percept::ActionFeedback { turn_id, .. } => {
+            turn_id.clone()
+        }
+    };
+    let watchdog = peas.turn_watchdog();
+    let turn_deadline = watchdog.map(|limit| tokio::time::Instant::now() + limit);
+    let cancel = TurnCancel::default();
+    let mut planning = tokio::task::spawn_blocking({
+        let peas = peas.clone();
+        let session_id = session_id.to_string();
+        let domain = domain.to_string();
+        let persisted = persisted.clone();
+        let cancel = cancel.clone();
+        move || peas.stream_percept_effects(&session_id, &domain, percept, &persisted, &cancel)
+    });
+    let mut effects: Option<EffectStream> = None;
+
+    let mut cancelled = false;
+    let mut stalled = false;
+    while !cancelled {
+        let effect = tokio::select! {
+            planned = &mut planning, if effects.is_none() => {
+                match planned.context("chat turn planning task failed")? {
+                    Ok(stream) => {
+                        effects = Some(stream);
+                        continue;
+                    }
+                    Err(error) => {
+                        let code = if let Some(peas_error) = error.downcast_ref::<PeasError>() {
+                            peas_error.code()
+                        } else if error.downcast_ref::<PluginLimitError>().is_some() {
+                            ErrorCode::LimitExceeded
+                        } else {
+                            return Err(error);
+                        };
+                        let response = AgentSocketMessage::Error {
+                            message: redact_secrets(&format!("{error:#}")),
+                            code,
+                        };
+                        writer
+                            .send(Message::Text(serde_json::to_string(&response)?))
+                            .await
+                            .context("failed to send percept error")?;
+                        return Ok(false);
+                    }
+                }
+            },
+            effect = next_effect(&mut effects) => match effect {
+                Some(effect) => effect?,
+                None => break,
+            },
+            _ = sleep_until_deadline(turn_deadline), if !stalled => {
+                stalled = true;
+                let limit = watchdog.unwrap_or_default().as_secs();
+                let total = peas.record_stalled_turn();
+                eprintln!(
+                    "turn {turn_id} in session {session_id} exceeded the {limit}s turn watchdog ({total} stalled turns since start)"
+                );
+                if !peas.turn_watchdog_cancels() {
+                    continue;
+                }
+                cancel.cancel();
+                cancelled = true;
+                Effect::TaskCompletion {
+                    turn_id: turn_id.clone(),
+                    status: "cancelled".to_string(),
+                    details: format!("turn cancelled by the watchdog after {limit}s"),
+                }
+            },
+            incoming = reader.stream.next() => match incoming {
+                Some(Ok(Message::Text(text))) if is_cancel_request(&text, session_id) => {
+                    cancel.cancel();
+                    cancelled = true;
+                    Effect::TaskCompletion {
+                        turn_id: turn_id.clone(),
+                        status: "cancelled".to_string(),
+                        details: "turn cancelled by user".to_string(),
+                    }
+                }
+                Some(Ok(message)) => {
+                    reader.deferred.push_back(message);
+                    continue;
+                }
+                Some(Err(error)) => {
+                    cancel.cancel();
+                    return Err(error.into());
+                }
+                None => {
+                    cancel.cancel();
+                    return Ok(false);
+                }
+            },
+        };
+
+        let response = AgentSocketMessage::EffectApplied {
+            session_id: session_id.to_string(),
+            domain: domain.to_string(),
+            effect: effect.clone(),
+        };
+        writer
+            .send(Message::Text(serde_json::to_string(&response)?))
+            .await
+            .context("failed to send effect response")?;
+        peas.record_effect(session_id, &effect)?;
+    }
+
+    Ok(!cancelled)
+}
+
+async fn next_effect(effects: &mut Option<EffectStream>) -> Option<anyhow::Result<Effect>> {
+    match effects {
+        Some(effects) => effects.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn sleep_until_deadline(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn resume_paused_queue(
+    writer: &mut SplitSink<WebSocketStream<TcpStream>, Message>,
+    reader: &mut UserSocketReader,
+    runtime: &Arc<Mutex<AgentRuntime>>,
+    queue: &mut PausedQueue,
+) -> anyhow::Result<()> {
+    let runtime_guard = runtime.lock().await;
+    if runtime_guard.mode != AgentMode::Running {
+        return Ok(());
+    }
+    let persisted = runtime_guard.persisted.clone();
+    let peas = runtime_guard.peas.clone();
+    drop(runtime_guard);
+
+    let response = AgentSocketMessage::ModeChanged {
+        mode: AgentMode::Running,
+        expired: queue.expire(),
+    };
+    writer
+        .send(Message::Text(serde_json::to_string(&response)?))
+        .await
+        .context("failed to send resume notice")?;
+    match persisted {
+        Some(persisted) => run_paused_queue(writer, reader, &peas, &persisted, queue).await,
+        None => Ok(()),
+    }
+}
+
+async fn run_paused_queue(
+    writer: &mut SplitSink<WebSocketStream<TcpStream>, Message>,
+    reader: &mut UserSocketReader,
+    peas: &PeasRuntime,
+    persisted: &PersistedAgentConfig,
+    queue: &mut PausedQueue,
+) -> anyhow::Result<()> {
+    queue.dropped = 0;
+    while let Some(QueuedPercept {
+        session_id,
+        domain,
+        percept,
+        ..
+    }) = queue.percepts.pop_front()
+    {
+        apply_percept(writer, reader, peas, persisted, session_id, domain, percept).await?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum QueueOverflow {
+    DropOldest,
+    DropNewest,
+}
+
+struct QueuedPercept {
+    session_id: String,
+    domain: String,
+    percept: Percept,
+    priority: u8,
+    queued_at: Instant,
+}
+
+struct PausedQueue {
+    percepts: VecDeque<QueuedPercept>,
+    limit: usize,
+    overflow: QueueOverflow,
+    dropped: usize,
+    ttl: Option<Duration>,
+}
+
+impl PausedQueue {
+    fn from_env() -> Self {
+        let limit = env::var(PAUSED_QUEUE_LIMIT_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_PAUSED_QUEUE_LIMIT);
+        let overflow = match env::var(PAUSED_QUEUE_OVERFLOW_ENV) {
+            Ok(value) if value.trim().eq_ignore_ascii_case("drop_newest") => {
+                QueueOverflow::DropNewest
+            }
+            _ => QueueOverflow::DropOldest,
+        };
+
+        let ttl = env::var(PAUSED_QUEUE_TTL_SECS_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|value| *value > 0)
+            .map(Duration::from_secs);
+
+        Self {
+            percepts: VecDeque::new(),
+            limit,
+            overflow,
+            dropped: 0,
+            ttl,
+        }
+    }
+
+    fn expire(&mut self) -> usize {
+        let Some(ttl) = self.ttl else {
+            return 0;
+        };
+        let before = self.percepts.len();
+        self.percepts
+            .retain(|queued| queued.queued_at.elapsed() <= ttl);
+        before - self.percepts.len()
+    }
+
+    fn push(&mut self, item: QueuedPercept) {
+        if self.percepts.len() >= self.limit {
+            self.dropped += 1;
+            let lowest = self
+                .percepts
+                .iter()
+                .map(|queued| queued.priority)
+                .min()
+                .unwrap_or_default();
+            if item.priority < lowest
+                || (self.overflow == QueueOverflow::DropNewest && item.priority == lowest)
+            {
+                return;
+            }
+            let index = match self.overflow {
+                QueueOverflow::DropNewest => self.percepts.len() - 1,
+                QueueOverflow::DropOldest => self
+                    .percepts
+                    .iter()
+                    .position(|queued| queued.priority == lowest)
+                    .unwrap_or_default(),
+            };
+            self.percepts.remove(index);
+        }
+
+        let index = self
+            .percepts
+            .iter()
+            .position(|queued| queued.priority < item.priority)
+            .unwrap_or(self.percepts.len());
+        self.percepts.insert(index, item);
+    }
+}
+
+struct RequestLog {
+    kind: String,
+    threshold: Duration,
+    started: Instant,
+}
+
+impl Drop for RequestLog {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        if elapsed >= self.threshold {
+            println!(
+                "socket request {} handled in {}ms",
+                self.kind,
+                elapsed.as_millis()
+            );
+        }
+    }
+}
+
+fn request_kind(text: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .and_then(|value| value.get("type")?.as_str().map(ToString::to_string))
+        .unwrap_or_else(|| "invalid".to_string())
+}
+
+struct UserSocketReader {
+    stream: SplitStream<WebSocketStream<TcpStream>>,
+    deferred: VecDeque<Message>,
+}
+
+impl UserSocketReader {
+    async fn next(&mut self) -> Option<Result<Message, tokio_tungstenite::tungstenite::Error>> {
+        match self.deferred.pop_front() {
+            Some(message) => Some(Ok(message)),
+            None => self.stream.next().await,
+        }
+    }
+}
+
+fn is_cancel_request(text: &str, session_id: &str) -> bool {
+    matches!(
+        serde_json::from_str::<AgentSocketMessage>(text),
+        Ok(AgentSocketMessage::CancelTurn { session_id: requested }) if requested == session_id
+    )
+}
+
 async fn complete_setup(
     runtime: &Arc<Mutex<AgentRuntime>>,
     discovery_url: &str,
@@ -571,6 +1627,10 @@ async fn complete_setup(
         );
     }
 
+    let agent_name = runtime_guard.agent_name.clone();
+    let peas = runtime_guard.peas.clone();
+    drop(runtime_guard);
+
     let settings = AgentSettings {
         workspace_dir: workspace_path.to_string_lossy().to_string(),
         port,
@@ -579,13 +1639,22 @@ async fn complete_setup(
     };
     let keys = AgentKeys { api_keys };
 
+    if !model_validation_skipped() {
+        peas.check_model(&settings.provider, &settings.model, &keys)
+            .await
+            .with_context(|| {
+                format!(
+                    "model '{}' could not be validated for provider '{}' (set {} to skip)",
+                    settings.model, settings.provider, SKIP_MODEL_VALIDATION_ENV
+                )
+            })?;
+    }
+
     let persisted = persist_config(&workspace_path, settings, keys)?;
     if !is_config_complete(&persisted) {
         bail!("setup data is incomplete: provider API key is missing");
     }
-
-    let agent_name = runtime_guard.agent_name.clone();
-    drop(runtime_guard);
+    record_model_change(&peas, &persisted.settings, "setup");
 
     upsert_launch_config(
         discovery_url,
@@ -599,9 +1668,220 @@ async fn complete_setup(
     runtime_guard.persisted = Some(persisted);
     runtime_guard.workspace_hint = Some(workspace_path);
     runtime_guard.mode = AgentMode::Running;
+    runtime_guard.models_ok = (!model_validation_skipped()).then_some(true);
     Ok(())
 }
 
+fn record_model_change(peas: &PeasRuntime, settings: &AgentSettings, source: &str) {
+    if let Err(error) = peas.record_model_change(
+        &settings.workspace_dir,
+        &settings.provider,
+        &settings.model,
+        source,
+    ) {
+        eprintln!(
+            "failed to record model configuration change: {}",
+            redact_secrets(&format!("{error:#}"))
+        );
+    }
+}
+
+fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn model_validation_skipped() -> bool {
+    env_flag(SKIP_MODEL_VALIDATION_ENV)
+}
+
+async fn remove_provider_key(
+    runtime: &Arc<Mutex<AgentRuntime>>,
+    provider: &str,
+) -> anyhow::Result<AgentMode> {
+    let provider = provider.trim();
+    if provider.is_empty() {
+        bail!(ConfigRequestError::Invalid(
+            "provider cannot be empty".to_string()
+        ));
+    }
+
+    let mut runtime_guard = runtime.lock().await;
+    let Some(persisted) = runtime_guard.persisted.clone() else {
+        bail!(ConfigRequestError::NotConfigured);
+    };
+
+    let mut keys = persisted.keys;
+    let before = keys.api_keys.len();
+    keys.api_keys
+        .retain(|entry| !entry.provider.eq_ignore_ascii_case(provider));
+    if keys.api_keys.len() == before {
+        bail!(ConfigRequestError::NotFound(format!(
+            "no API key is stored for provider '{provider}'"
+        )));
+    }
+
+    let workspace_path = normalize_workspace_dir(&persisted.settings.workspace_dir)?;
+    let persisted = persist_config(&workspace_path, persisted.settings, keys)?;
+    if !is_config_complete(&persisted) {
+        runtime_guard.mode = AgentMode::Setup;
+        runtime_guard.models_ok = None;
+    }
+    let mode = runtime_guard.mode;
+    runtime_guard.persisted = Some(persisted);
+    Ok(mode)
+}
+
+async fn export_config(
+    runtime: &Arc<Mutex<AgentRuntime>>,
+    path: &str,
+    include_keys: bool,
+) -> anyhow::Result<PathBuf> {
+    let runtime_guard = runtime.lock().await;
+    let peas = runtime_guard.peas.clone();
+    let Some(persisted) = runtime_guard.persisted.clone() else {
+        bail!("agent is missing persisted configuration");
+    };
+    drop(runtime_guard);
+
+    let bundle = ConfigBundle {
+        version: CONFIG_BUNDLE_VERSION,
+        plugins: peas.plugin_overrides(&persisted.settings.workspace_dir)?,
+        provider: persisted.settings.provider,
+        model: persisted.settings.model,
+        keys_included: include_keys,
+        api_keys: if include_keys {
+            persisted.keys.api_keys
+        } else {
+            Vec::new()
+        },
+    };
+
+    let bundle_path = resolve_workspace_path(&persisted.settings.workspace_dir, path)?;
+    write_config_bundle(&bundle_path, &bundle)?;
+    Ok(bundle_path)
+}
+
+async fn import_config(
+    runtime: &Arc<Mutex<AgentRuntime>>,
+    path: &str,
+    include_keys: bool,
+) -> anyhow::Result<(PathBuf, AgentMode, usize)> {
+    let mut runtime_guard = runtime.lock().await;
+    let peas = runtime_guard.peas.clone();
+    let Some(previous) = runtime_guard.persisted.clone() else {
+        bail!(ConfigRequestError::NotConfigured);
+    };
+
+    let workspace_dir = previous.settings.workspace_dir.clone();
+    let bundle_path = resolve_workspace_path(&workspace_dir, path)
+        .map_err(|error| ConfigRequestError::Invalid(format!("{error:#}")))?;
+    let bundle = read_config_bundle(&bundle_path).map_err(|error| {
+        let io_kind = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<io::Error>())
+            .map(io::Error::kind);
+        match io_kind {
+            Some(io::ErrorKind::NotFound) => {
+                ConfigRequestError::NotFound(format!("{error:#}")).into()
+            }
+            Some(_) => error,
+            None => ConfigRequestError::Invalid(format!("{error:#}")).into(),
+        }
+    })?;
+
+    let mut keys = previous.keys.clone();
+    let mut keys_imported = 0;
+    if include_keys {
+        for imported in bundle.api_keys {
+            keys.api_keys
+                .retain(|entry| !entry.provider.eq_ignore_ascii_case(&imported.provider));
+            keys.api_keys.push(imported);
+            keys_imported += 1;
+        }
+    }
+    let settings = AgentSettings {
+        provider: bundle.provider,
+        model: bundle.model,
+        ..previous.settings.clone()
+    };
+
+    let workspace_path = normalize_workspace_dir(&workspace_dir)?;
+    let persisted = persist_imported_config(&workspace_path, previous, settings, keys, || {
+        peas.apply_plugin_overrides(&workspace_dir, &bundle.plugins)
+    })?;
+
+    record_model_change(&peas, &persisted.settings, "import");
+    if !is_config_complete(&persisted) {
+        runtime_guard.mode = AgentMode::Setup;
+    } else if runtime_guard.mode == AgentMode::Setup {
+        runtime_guard.mode = AgentMode::Running;
+    }
+    runtime_guard.models_ok = None;
+    runtime_guard.persisted = Some(persisted);
+    Ok((bundle_path, runtime_guard.mode, keys_imported))
+}
+
+fn client_percept_rejection(percept: &Percept) -> Option<AgentSocketMessage> {
+    match percept {
+        Percept::UserText { .. } => None,
+        Percept::ActionFeedback { .. } => Some(AgentSocketMessage::Error {
+            message: "action_feedback percepts are generated by the agent".to_string(),
+            code: ErrorCode::InvalidRequest,
+        }),
+    }
+}
+
+fn batch_percept_problem(index: usize, percept: &Percept) -> Option<String> {
+    match percept {
+        _ if index >= MAX_PERCEPT_BATCH => Some(format!(
+            "batch limit of {MAX_PERCEPT_BATCH} percepts exceeded"
+        )),
+        Percept::UserText { text, .. } if text.trim().is_empty() => {
+            Some("percept text is empty".to_string())
+        }
+        Percept::UserText { .. } => None,
+        Percept::ActionFeedback { .. } => {
+            Some("only user_text percepts can be batched".to_string())
+        }
+    }
+}
+
+fn persist_imported_config(
+    workspace_path: &Path,
+    previous: PersistedAgentConfig,
+    settings: AgentSettings,
+    keys: AgentKeys,
+    apply_plugins: impl FnOnce() -> anyhow::Result<()>,
+) -> anyhow::Result<PersistedAgentConfig> {
+    let applied = persist_config(workspace_path, settings, keys).and_then(|persisted| {
+        apply_plugins()?;
+        Ok(persisted)
+    });
+    match applied {
+        Ok(persisted) => Ok(persisted),
+        Err(error) => {
+            persist_config(workspace_path, previous.settings, previous.keys)
+                .context("failed to restore settings after import error")?;
+            Err(error.context("previous settings were restored"))
+        }
+    }
+}
+
+fn resolve_workspace_path(workspace_dir: &str, path: &str) -> anyhow::Result<PathBuf> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        bail!("path cannot be empty");
+    }
+    let path = PathBuf::from(trimmed);
+    if path.is_relative() {
+        return Ok(PathBuf::from(workspace_dir).join(path));
+    }
+    Ok(path)
+}
+
 async fn upsert_launch_config(
     discovery_url: &str,
     workspace_dir: String,
@@ -647,3 +1927,194 @@ async fn upsert_launch_config(
 
     bail!("discovery server closed before upsert confirmation")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use looper_common::ProviderApiKey;
+    use std::fs;
+
+    fn paused_queue(limit: usize, overflow: QueueOverflow) -> PausedQueue {
+        PausedQueue {
+            percepts: VecDeque::new(),
+            limit,
+            overflow,
+            dropped: 0,
+            ttl: None,
+        }
+    }
+
+    fn queued(text: &str, priority: u8) -> QueuedPercept {
+        QueuedPercept {
+            session_id: "sess-1".to_string(),
+            domain: "chat".to_string(),
+            percept: Percept::UserText {
+                turn_id: format!("turn-{text}"),
+                text: text.to_string(),
+            },
+            priority,
+            queued_at: Instant::now(),
+        }
+    }
+
+    fn texts(queue: &PausedQueue) -> Vec<&str> {
+        queue
+            .percepts
+            .iter()
+            .map(|queued| match &queued.percept {
+                Percept::UserText { text, .. } | Percept::ActionFeedback { text, .. } => {
+                    text.as_str()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn urgent_percepts_queue_ahead_in_arrival_order() {
+        let mut queue = paused_queue(10, QueueOverflow::DropOldest);
+        queue.push(queued("a", 0));
+        queue.push(queued("u1", 1));
+        queue.push(queued("b", 0));
+        queue.push(queued("u2", 1));
+        assert_eq!(texts(&queue), ["u1", "u2", "a", "b"]);
+        assert_eq!(queue.dropped, 0);
+    }
+
+    #[test]
+    fn full_queue_drops_oldest_routine_percept() {
+        let mut queue = paused_queue(2, QueueOverflow::DropOldest);
+        queue.push(queued("a", 0));
+        queue.push(queued("b", 0));
+        queue.push(queued("c", 0));
+        assert_eq!(texts(&queue), ["b", "c"]);
+        assert_eq!(queue.dropped, 1);
+    }
+
+    #[test]
+    fn full_queue_drops_newest_routine_percept() {
+        let mut queue = paused_queue(2, QueueOverflow::DropNewest);
+        queue.push(queued("a", 0));
+        queue.push(queued("b", 0));
+        queue.push(queued("c", 0));
+        assert_eq!(texts(&queue), ["a", "b"]);
+        assert_eq!(queue.dropped, 1);
+    }
+
+    #[test]
+    fn urgent_percept_displaces_routine_percept() {
+        let mut queue = paused_queue(2, QueueOverflow::DropNewest);
+        queue.push(queued("a", 0));
+        queue.push(queued("b", 0));
+        queue.push(queued("u", 1));
+        assert_eq!(texts(&queue), ["u", "a"]);
+
+        let mut queue = paused_queue(2, QueueOverflow::DropOldest);
+        queue.push(queued("a", 0));
+        queue.push(queued("b", 0));
+        queue.push(queued("u", 1));
+        assert_eq!(texts(&queue), ["u", "b"]);
+    }
+
+    #[test]
+    fn routine_percept_is_dropped_when_queue_is_all_urgent() {
+        let mut queue = paused_queue(2, QueueOverflow::DropOldest);
+        queue.push(queued("u1", 1));
+        queue.push(queued("u2", 1));
+        queue.push(queued("a", 0));
+        assert_eq!(texts(&queue), ["u1", "u2"]);
+        assert_eq!(queue.dropped, 1);
+    }
+
+    #[test]
+    fn expire_drops_percepts_older_than_ttl() {
+        let mut queue = paused_queue(10, QueueOverflow::DropOldest);
+        queue.ttl = Some(Duration::from_secs(60));
+        let mut stale = queued("stale", 1);
+        stale.queued_at = Instant::now() - Duration::from_secs(120);
+        queue.push(stale);
+        queue.push(queued("fresh", 0));
+        assert_eq!(queue.expire(), 1);
+        assert_eq!(texts(&queue), ["fresh"]);
+    }
+
+    #[test]
+    fn failed_import_restores_previous_settings() {
+        let workspace = env::temp_dir().join(format!(
+            "looper-import-test-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let settings = |provider: &str, model: &str| AgentSettings {
+            workspace_dir: workspace.to_string_lossy().to_string(),
+            port: 11000,
+            provider: provider.to_string(),
+            model: model.to_string(),
+        };
+        let keys = |api_key: &str| AgentKeys {
+            api_keys: vec![ProviderApiKey {
+                provider: "openai".to_string(),
+                api_key: api_key.to_string(),
+            }],
+        };
+        let previous = persist_config(
+            &workspace,
+            settings("openai", "gpt-old"),
+            keys("old-key-0123"),
+        )
+        .unwrap();
+
+        let error = persist_imported_config(
+            &workspace,
+            previous.clone(),
+            settings("anthropic", "claude-new"),
+            keys("new-key-0123"),
+            || bail!("plugins are not installed"),
+        )
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("previous settings were restored"));
+        let restored = load_persisted_config(&workspace).unwrap().unwrap();
+        assert_eq!(restored.settings.provider, "openai");
+        assert_eq!(restored.settings.model, "gpt-old");
+        assert_eq!(restored.keys.api_keys[0].api_key, "old-key-0123");
+
+        let imported = persist_imported_config(
+            &workspace,
+            previous,
+            settings("anthropic", "claude-new"),
+            keys("new-key-0123"),
+            || Ok(()),
+        )
+        .unwrap();
+        assert_eq!(imported.settings.model, "claude-new");
+        let loaded = load_persisted_config(&workspace).unwrap().unwrap();
+        assert_eq!(loaded.settings.provider, "anthropic");
+        let _ = fs::remove_dir_all(workspace);
+    }
+
+    #[test]
+    fn client_sent_action_feedback_is_rejected() {
+        let feedback = Percept::ActionFeedback {
+            turn_id: "turn-1-feedback-1".to_string(),
+            text: "Results of earlier actions".to_string(),
+            depth: 0,
+        };
+        let Some(AgentSocketMessage::Error { message, code }) = client_percept_rejection(&feedback)
+        else {
+            panic!("client feedback percept was accepted");
+        };
+        assert_eq!(code, ErrorCode::InvalidRequest);
+        assert!(message.contains("generated by the agent"));
+        assert!(client_percept_rejection(&queued("hello", 0).percept).is_none());
+
+        assert_eq!(
+            batch_percept_problem(0, &feedback).as_deref(),
+            Some("only user_text percepts can be batched")
+        );
+        assert!(batch_percept_problem(0, &queued("hello", 0).percept).is_none());
+        assert!(batch_percept_problem(0, &queued(" ", 0).percept).is_some());
+        assert!(batch_percept_problem(MAX_PERCEPT_BATCH, &queued("hello", 0).percept).is_some());
+    }
+}
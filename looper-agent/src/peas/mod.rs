@@ -1,13 +1,15 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
+use std::fmt;
 use std::fs;
-use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
 use std::pin::Pin;
-use std::process::{Command, Stdio};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, mpsc};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, bail};
 use async_stream::try_stream;
@@ -16,26 +18,161 @@ use fiddlesticks::{
     parse_provider_id,
 };
 use futures_util::{Stream, StreamExt};
-use globset::Glob;
-use looper_common::{Effect, Percept, PlannedAction, PlannedActionStatus, SessionOrigin};
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use looper_common::{
+    AuditEntry, ChatSearchHit, DenyReason, Effect, ErrorCode, ExportFormat, ModelChange, Percept,
+    PlannedAction, PlannedActionStatus, PluginStatus, SessionOrigin, TurnTiming, write_file_atomic,
+    write_file_atomic_with,
+};
 use regex::Regex;
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, params};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use walkdir::WalkDir;
 
-use crate::settings::AgentKeys;
+use crate::settings::{
+    AgentKeys, PersistedAgentConfig, PluginOverride, env_flag, redact_secrets, register_secret,
+};
 
 const CHAT_DOMAIN: &str = "chat";
+const DEFAULT_PLUGIN_TIMEOUT_MS: u64 = 30_000;
+const DEFAULT_PLUGIN_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+const MIN_PLUGIN_TIMEOUT_MS: u64 = 100;
+const PLUGIN_READER_GRACE: Duration = Duration::from_millis(500);
+const CHAT_RETENTION_SESSIONS_ENV: &str = "LOOPER_CHAT_RETENTION_SESSIONS";
+const CHAT_RETENTION_DAYS_ENV: &str = "LOOPER_CHAT_RETENTION_DAYS";
+const MILLIS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+const SQLITE_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+const MODEL_HEALTH_TIMEOUT: Duration = Duration::from_secs(20);
+const DENO_CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+const SOUL_PATH_ENV: &str = "LOOPER_SOUL_PATH";
+const PLUGIN_API_VERSION: u32 = 1;
+const NATIVE_FILESYSTEM_ACTUATORS: [&str; 4] = [
+    "filesystem_grep",
+    "filesystem_glob",
+    "filesystem_read",
+    "filesystem_write",
+];
+const FEEDBACK_DEPTH_ENV: &str = "LOOPER_MAX_FEEDBACK_DEPTH";
+const DEFAULT_MAX_FEEDBACK_DEPTH: u32 = 2;
+const MAX_ACTIONS_PER_TURN_ENV: &str = "LOOPER_MAX_ACTIONS_PER_TURN";
+const BREAKER_FAILURES_ENV: &str = "LOOPER_BREAKER_FAILURES";
+const BREAKER_WINDOW_SECS_ENV: &str = "LOOPER_BREAKER_WINDOW_SECS";
+const BREAKER_COOLDOWN_SECS_ENV: &str = "LOOPER_BREAKER_COOLDOWN_SECS";
+const DEFAULT_BREAKER_FAILURES: u32 = 3;
+const DEFAULT_BREAKER_WINDOW_SECS: u64 = 300;
+const DEFAULT_BREAKER_COOLDOWN_SECS: u64 = 60;
+const ALLOWED_WINDOWS_ENV: &str = "LOOPER_ALLOWED_WINDOWS";
+const UTC_OFFSET_ENV: &str = "LOOPER_UTC_OFFSET";
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const ACTION_OVERFLOW_ENV: &str = "LOOPER_ACTION_OVERFLOW";
+const ACTUATOR_APPROVAL_ENV: &str = "LOOPER_ACTUATOR_APPROVAL";
+const RISK_PATTERNS_ENV: &str = "LOOPER_RISK_PATTERNS";
+const DEFAULT_RISK_PATTERNS: [&str; 8] = [
+    "rm -rf",
+    "rm -fr",
+    "git reset --hard",
+    "git clean -fd",
+    "git push --force",
+    "drop table",
+    "drop database",
+    "mkfs",
+];
+const TURN_WATCHDOG_SECS_ENV: &str = "LOOPER_TURN_WATCHDOG_SECS";
+const TURN_WATCHDOG_CANCEL_ENV: &str = "LOOPER_TURN_WATCHDOG_CANCEL";
+const DEFAULT_ACTUATOR_APPROVAL: [(&str, bool); 2] =
+    [("plugin_process", true), ("native_filesystem", false)];
+const CHAT_PERCEPT_SENSOR: &str = "terminal_chat_percept";
+const ACTION_FEEDBACK_SENSOR: &str = "action_feedback";
 static NEXT_ID: AtomicU64 = AtomicU64::new(1);
 
 #[derive(Debug, Clone)]
 pub struct PeasRuntime {
     agent_id: String,
+    display_name: Option<String>,
     db_path: PathBuf,
-    builtin_plugins: Vec<LoadedPlugin>,
+    builtin_plugins: Arc<Mutex<Vec<LoadedPlugin>>>,
     pending_approvals: Arc<Mutex<HashMap<String, Vec<PendingApproval>>>>,
+    pending_feedback: Arc<Mutex<HashMap<String, Percept>>>,
+    max_feedback_depth: u32,
+    action_limit: ActionLimit,
+    breaker_config: BreakerConfig,
+    breakers: Arc<Mutex<HashMap<(String, String), CircuitBreaker>>>,
+    schedule: Option<ActionSchedule>,
+    approval_defaults: BTreeMap<String, bool>,
+    risk_patterns: Vec<String>,
+    turn_watchdog: Option<Duration>,
+    turn_watchdog_cancels: bool,
+    stalled_turns: Arc<AtomicU64>,
+    retention: ChatRetention,
+    soul_cache: Arc<Mutex<HashMap<PathBuf, CachedSoul>>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeSnapshot {
+    #[serde(default)]
+    pending_approvals: BTreeMap<String, Vec<PendingApproval>>,
+    #[serde(default)]
+    pending_feedback: BTreeMap<String, Percept>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ActionOverflow {
+    Defer,
+    Drop,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActionLimit {
+    max_per_turn: Option<usize>,
+    overflow: ActionOverflow,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BreakerConfig {
+    failures: u32,
+    window: Duration,
+    cooldown: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CircuitBreaker {
+    consecutive_failures: u32,
+    first_failure_at: Instant,
+    opened_at: Option<Instant>,
+}
+
+#[derive(Debug, Clone)]
+struct ActionSchedule {
+    windows: Vec<TimeWindow>,
+    utc_offset_minutes: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TimeWindow {
+    days: [bool; 7],
+    start_minute: u32,
+    end_minute: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AgentStartRecord {
+    pub first_started_at: i64,
+    pub restart_count: u64,
+}
+
+#[derive(Debug, Clone)]
+struct CachedSoul {
+    modified: SystemTime,
+    len: u64,
+    prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ChatRetention {
+    keep_sessions: Option<usize>,
+    max_age_days: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -44,6 +181,7 @@ struct LoadedPlugin {
     root_dir: PathBuf,
     entry_path: PathBuf,
     manifest: PluginManifest,
+    env: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -51,8 +189,12 @@ struct PluginManifest {
     name: String,
     description: String,
     version: String,
+    #[serde(default)]
+    api_version: Option<u32>,
     entry: String,
     permissions: PluginPermissions,
+    #[serde(default)]
+    limits: PluginLimits,
     peas: PluginPeas,
 }
 
@@ -62,6 +204,18 @@ struct PluginPermissions {
     read: Vec<String>,
     #[serde(default)]
     run: Vec<String>,
+    #[serde(default)]
+    write: Vec<String>,
+    #[serde(default)]
+    net: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+struct PluginLimits {
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    max_output_bytes: Option<usize>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -102,6 +256,16 @@ struct PluginPerformanceReward {
     weight: Option<f64>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum PerceptTransform {
+    StripAnsi,
+    Trim,
+    Lowercase,
+    Regex { find: String, replace: String },
+    Truncate { max_chars: usize },
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct PluginEnvironment {
     name: String,
@@ -114,6 +278,14 @@ struct PluginComponent {
     description: String,
     #[serde(default)]
     executor: Option<String>,
+    #[serde(default)]
+    workspace_root: Option<String>,
+    #[serde(default)]
+    transforms: Vec<PerceptTransform>,
+    #[serde(default)]
+    require_approval: Option<bool>,
+    #[serde(default)]
+    soul: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -131,6 +303,8 @@ struct WorkspacePluginState {
     source: Option<String>,
     #[serde(default)]
     version: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -138,6 +312,7 @@ struct ChatPluginPerceptInput {
     session_id: String,
     turn_id: String,
     text: String,
+    sensor: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -167,6 +342,10 @@ struct PlannedActionSpec {
     actuator: String,
     #[serde(default)]
     args: Value,
+    #[serde(default)]
+    depends_on: Option<usize>,
+    #[serde(default)]
+    feedback: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -182,6 +361,12 @@ struct FilesystemActionPlan {
     file_path: Option<String>,
     #[serde(default)]
     max_lines: Option<usize>,
+    #[serde(default)]
+    contents: Option<String>,
+    #[serde(default)]
+    append: bool,
+    #[serde(default)]
+    respect_gitignore: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -189,6 +374,38 @@ struct ActionOutcome {
     status: String,
     details: String,
     sensor_output: String,
+    structured: Option<Value>,
+    deny_reason: Option<DenyReason>,
+}
+
+struct NativeCommandOutput {
+    stdout: String,
+    stderr: String,
+    details: String,
+    status: String,
+    structured: Option<Value>,
+}
+
+impl NativeCommandOutput {
+    fn completed(stdout: String, stderr: String, details: String, structured: Value) -> Self {
+        Self {
+            stdout,
+            stderr,
+            details,
+            status: "completed".to_string(),
+            structured: Some(structured),
+        }
+    }
+
+    fn failed(stderr: String, details: String) -> Self {
+        Self {
+            stdout: String::new(),
+            stderr,
+            details,
+            status: "failed".to_string(),
+            structured: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -207,9 +424,127 @@ struct PluginActuatorOutput {
     details: String,
     #[serde(default)]
     sensor_output: Option<String>,
+    #[serde(default)]
+    structured: Option<Value>,
 }
 
-#[derive(Debug, Clone)]
+enum PluginProcessExit {
+    Completed(Output),
+    TimedOut,
+    OutputLimitExceeded,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TurnCancel(Arc<AtomicBool>);
+
+impl TurnCancel {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug)]
+pub enum PluginLimitError {
+    TimedOut {
+        plugin: String,
+        timeout_ms: u64,
+    },
+    OutputLimitExceeded {
+        plugin: String,
+        max_output_bytes: usize,
+    },
+}
+
+impl fmt::Display for PluginLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut { plugin, timeout_ms } => {
+                write!(f, "plugin '{plugin}' timed out after {timeout_ms} ms")
+            }
+            Self::OutputLimitExceeded {
+                plugin,
+                max_output_bytes,
+            } => write!(
+                f,
+                "plugin '{plugin}' exceeded the output limit of {max_output_bytes} bytes"
+            ),
+        }
+    }
+}
+
+impl PluginLimitError {
+    fn deny_reason(&self) -> DenyReason {
+        match self {
+            Self::TimedOut { .. } => DenyReason::TimedOut,
+            Self::OutputLimitExceeded { .. } => DenyReason::OutputLimitExceeded,
+        }
+    }
+}
+
+impl std::error::Error for PluginLimitError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeasError {
+    PluginNotInstalled {
+        plugin: String,
+        workspace_dir: String,
+    },
+    IncompatiblePlugin {
+        plugin: String,
+        required: u32,
+    },
+    OnlyChatPlugin {
+        plugin: String,
+        verb: String,
+    },
+    NoChatPlugin,
+}
+
+impl fmt::Display for PeasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PluginNotInstalled {
+                plugin,
+                workspace_dir,
+            } => write!(
+                f,
+                "plugin '{plugin}' is not installed for workspace {workspace_dir}"
+            ),
+            Self::IncompatiblePlugin { plugin, required } => write!(
+                f,
+                "plugin '{plugin}' requires plugin API version {required}; this agent supports version {PLUGIN_API_VERSION}"
+            ),
+            Self::OnlyChatPlugin { plugin, verb } => write!(
+                f,
+                "cannot {verb} plugin '{plugin}'; it is the only enabled plugin with the 'terminal_chat_percept' sensor and 'chat_effect_append' actuator"
+            ),
+            Self::NoChatPlugin => write!(
+                f,
+                "no active plugin can process chat percepts (missing sensor 'terminal_chat_percept' and actuator 'chat_effect_append')"
+            ),
+        }
+    }
+}
+
+impl PeasError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::PluginNotInstalled { .. } => ErrorCode::NotFound,
+            Self::IncompatiblePlugin { .. } => ErrorCode::InvalidRequest,
+            Self::OnlyChatPlugin { .. } => ErrorCode::Conflict,
+            Self::NoChatPlugin => ErrorCode::NotConfigured,
+        }
+    }
+}
+
+impl std::error::Error for PeasError {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct PendingApproval {
     action: PlannedAction,
     reason: String,
@@ -256,14 +591,21 @@ fn default_completed_status() -> String {
     "completed".to_string()
 }
 
-type EffectStream = Pin<Box<dyn Stream<Item = anyhow::Result<Effect>> + Send>>;
+pub type EffectStream = Pin<Box<dyn Stream<Item = anyhow::Result<Effect>> + Send>>;
 
 impl PeasRuntime {
-    pub fn new(agent_id: String) -> anyhow::Result<Self> {
-        let db_path = chats_db_path()?;
+    pub fn new(agent_id: String, agent_name: Option<String>) -> anyhow::Result<Self> {
+        Self::with_db_path(agent_id, agent_name, chats_db_path()?)
+    }
+
+    fn with_db_path(
+        agent_id: String,
+        agent_name: Option<String>,
+        db_path: PathBuf,
+    ) -> anyhow::Result<Self> {
         initialize_db(&db_path)?;
 
-        let builtin_plugins = load_plugins(&Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins"))?;
+        let builtin_plugins = load_plugins(&builtin_plugins_dir())?;
 
         if builtin_plugins.is_empty() {
             bail!("no PEAS plugins were loaded");
@@ -271,9 +613,46 @@ impl PeasRuntime {
 
         Ok(Self {
             agent_id,
+            display_name: agent_name
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty()),
             db_path,
-            builtin_plugins,
+            builtin_plugins: Arc::new(Mutex::new(builtin_plugins)),
             pending_approvals: Arc::new(Mutex::new(HashMap::new())),
+            pending_feedback: Arc::new(Mutex::new(HashMap::new())),
+            max_feedback_depth: env::var(FEEDBACK_DEPTH_ENV)
+                .ok()
+                .and_then(|raw| raw.trim().parse::<u32>().ok())
+                .unwrap_or(DEFAULT_MAX_FEEDBACK_DEPTH),
+            action_limit: ActionLimit {
+                max_per_turn: positive_env(MAX_ACTIONS_PER_TURN_ENV),
+                overflow: match env::var(ACTION_OVERFLOW_ENV) {
+                    Ok(value) if value.trim().eq_ignore_ascii_case("drop") => ActionOverflow::Drop,
+                    _ => ActionOverflow::Defer,
+                },
+            },
+            breaker_config: BreakerConfig {
+                failures: positive_env(BREAKER_FAILURES_ENV).unwrap_or(DEFAULT_BREAKER_FAILURES),
+                window: Duration::from_secs(
+                    positive_env(BREAKER_WINDOW_SECS_ENV).unwrap_or(DEFAULT_BREAKER_WINDOW_SECS),
+                ),
+                cooldown: Duration::from_secs(
+                    positive_env(BREAKER_COOLDOWN_SECS_ENV)
+                        .unwrap_or(DEFAULT_BREAKER_COOLDOWN_SECS),
+                ),
+            },
+            breakers: Arc::new(Mutex::new(HashMap::new())),
+            schedule: schedule_from_env()?,
+            approval_defaults: approval_defaults_from_env()?,
+            risk_patterns: risk_patterns_from_env(),
+            turn_watchdog: positive_env(TURN_WATCHDOG_SECS_ENV).map(Duration::from_secs),
+            turn_watchdog_cancels: env_flag(TURN_WATCHDOG_CANCEL_ENV),
+            stalled_turns: Arc::new(AtomicU64::new(0)),
+            retention: ChatRetention {
+                keep_sessions: positive_env(CHAT_RETENTION_SESSIONS_ENV),
+                max_age_days: positive_env(CHAT_RETENTION_DAYS_ENV),
+            },
+            soul_cache: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
@@ -296,6 +675,13 @@ impl PeasRuntime {
             ],
         )
         .context("failed to persist new chat session")?;
+
+        if let Err(error) = self.apply_retention() {
+            eprintln!(
+                "chat retention pruning failed: {}",
+                redact_secrets(&format!("{error:#}"))
+            );
+        }
         Ok(session_id)
     }
 
@@ -309,34 +695,313 @@ impl PeasRuntime {
         Ok(())
     }
 
-    pub fn install_workspace_plugin(
+    pub fn prune_sessions_older_than(&self, cutoff_millis: i64) -> anyhow::Result<usize> {
+        let mut conn = open_db(&self.db_path)?;
+        let tx = conn
+            .transaction()
+            .context("failed to begin chat pruning transaction")?;
+        tx.execute(
+            "DELETE FROM events WHERE session_id IN (
+                 SELECT id FROM sessions
+                 WHERE agent_id = ?1 AND ended_at IS NOT NULL AND ended_at < ?2
+             )",
+            params![self.agent_id, cutoff_millis],
+        )
+        .context("failed to prune events for expired sessions")?;
+        let removed = tx
+            .execute(
+                "DELETE FROM sessions
+                 WHERE agent_id = ?1 AND ended_at IS NOT NULL AND ended_at < ?2",
+                params![self.agent_id, cutoff_millis],
+            )
+            .context("failed to prune expired sessions")?;
+        tx.commit()
+            .context("failed to commit chat pruning transaction")?;
+        Ok(removed)
+    }
+
+    pub fn prune_sessions_keep_last(&self, keep: usize) -> anyhow::Result<usize> {
+        let keep = keep as i64;
+        let mut conn = open_db(&self.db_path)?;
+        let tx = conn
+            .transaction()
+            .context("failed to begin chat pruning transaction")?;
+        tx.execute(
+            "DELETE FROM events WHERE session_id IN (
+                 SELECT id FROM sessions
+                 WHERE agent_id = ?1 AND ended_at IS NOT NULL AND id NOT IN (
+                     SELECT id FROM sessions WHERE agent_id = ?1
+                     ORDER BY started_at DESC LIMIT ?2
+                 )
+             )",
+            params![self.agent_id, keep],
+        )
+        .context("failed to prune events for old sessions")?;
+        let removed = tx
+            .execute(
+                "DELETE FROM sessions
+                 WHERE agent_id = ?1 AND ended_at IS NOT NULL AND id NOT IN (
+                     SELECT id FROM sessions WHERE agent_id = ?1
+                     ORDER BY started_at DESC LIMIT ?2
+                 )",
+                params![self.agent_id, keep],
+            )
+            .context("failed to prune old sessions")?;
+        tx.commit()
+            .context("failed to commit chat pruning transaction")?;
+        Ok(removed)
+    }
+
+    fn apply_retention(&self) -> anyhow::Result<()> {
+        if let Some(max_age_days) = self.retention.max_age_days {
+            let max_age_millis = u128::from(max_age_days) * u128::from(MILLIS_PER_DAY);
+            let cutoff = now_millis().saturating_sub(max_age_millis);
+            self.prune_sessions_older_than(cutoff as i64)?;
+        }
+        if let Some(keep_sessions) = self.retention.keep_sessions {
+            self.prune_sessions_keep_last(keep_sessions)?;
+        }
+        Ok(())
+    }
+
+    pub fn record_agent_start(
         &self,
         workspace_dir: &str,
+        port: u16,
+    ) -> anyhow::Result<AgentStartRecord> {
+        let conn = open_db(&self.db_path)?;
+        conn.execute(
+            "INSERT INTO agent_workspace_starts
+                 (workspace_dir, port, first_started_at, last_started_at, restart_count)
+             VALUES (?1, ?2, ?3, ?3, 0)
+             ON CONFLICT(workspace_dir, port) DO UPDATE SET
+                 last_started_at = excluded.last_started_at,
+                 restart_count = restart_count + 1",
+            params![workspace_dir, port, now_millis() as i64],
+        )
+        .context("failed to record agent start")?;
+        conn.query_row(
+            "SELECT first_started_at, restart_count FROM agent_workspace_starts
+             WHERE workspace_dir = ?1 AND port = ?2",
+            params![workspace_dir, port],
+            |row| {
+                Ok(AgentStartRecord {
+                    first_started_at: row.get(0)?,
+                    restart_count: row.get::<_, i64>(1)?.max(0) as u64,
+                })
+            },
+        )
+        .context("failed to read agent start record")
+    }
+
+    pub fn check_store(&self) -> anyhow::Result<()> {
+        let conn = open_db(&self.db_path)?;
+        conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))
+            .context("chat store did not answer a trivial query")?;
+        Ok(())
+    }
+
+    pub fn search_chat_messages(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ChatSearchHit>> {
+        let conn = open_db(&self.db_path)?;
+        search_chat_events(&conn, &self.agent_id, query, limit)
+    }
+
+    pub fn record_model_change(
+        &self,
+        workspace_dir: &str,
+        provider: &str,
+        model: &str,
         source: &str,
-    ) -> anyhow::Result<String> {
-        let source_path = PathBuf::from(source.trim());
-        if source_path.as_os_str().is_empty() {
-            bail!("plugin source path cannot be empty");
-        }
-        if !source_path.exists() {
-            bail!(
-                "plugin source path does not exist: {}",
-                source_path.display()
-            );
+    ) -> anyhow::Result<()> {
+        let conn = open_db(&self.db_path)?;
+        let latest = conn
+            .query_row(
+                "SELECT provider, model FROM model_config_history
+                 WHERE workspace_dir = ?1
+                 ORDER BY changed_at DESC, rowid DESC
+                 LIMIT 1",
+                params![workspace_dir],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()
+            .context("failed to read latest model configuration")?;
+        if latest.is_some_and(|(last_provider, last_model)| {
+            last_provider == provider && last_model == model
+        }) {
+            return Ok(());
         }
-        if !source_path.is_dir() {
-            bail!(
-                "plugin source must be a directory containing looper-plugin.json: {}",
-                source_path.display()
-            );
+
+        conn.execute(
+            "INSERT INTO model_config_history (id, workspace_dir, provider, model, source, changed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                next_id("model"),
+                workspace_dir,
+                provider,
+                model,
+                source,
+                now_millis() as i64
+            ],
+        )
+        .context("failed to record model configuration change")?;
+        Ok(())
+    }
+
+    pub fn model_history(
+        &self,
+        workspace_dir: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ModelChange>> {
+        let conn = open_db(&self.db_path)?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT provider, model, source, changed_at FROM model_config_history
+                 WHERE workspace_dir = ?1
+                 ORDER BY changed_at DESC, rowid DESC
+                 LIMIT ?2",
+            )
+            .context("failed to prepare model history query")?;
+        let entries = stmt
+            .query_map(params![workspace_dir, limit.clamp(1, 200) as i64], |row| {
+                Ok(ModelChange {
+                    provider: row.get(0)?,
+                    model: row.get(1)?,
+                    source: row.get(2)?,
+                    changed_at: row.get(3)?,
+                })
+            })
+            .context("failed to run model history query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to read model history")?;
+        Ok(entries)
+    }
+
+    pub fn query_audit(
+        &self,
+        actuator: Option<&str>,
+        since: Option<i64>,
+        until: Option<i64>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<AuditEntry>> {
+        let conn = open_db(&self.db_path)?;
+        let limit = limit.clamp(1, 500) as i64;
+        let mut stmt = conn
+            .prepare(
+                "SELECT session_id, turn_id, action_id, plugin, actuator, status, deny_reason, details, created_at
+                 FROM action_audit
+                 WHERE agent_id = ?5
+                   AND (?1 IS NULL OR actuator = ?1)
+                   AND (?2 IS NULL OR created_at >= ?2)
+                   AND (?3 IS NULL OR created_at <= ?3)
+                 ORDER BY created_at DESC
+                 LIMIT ?4",
+            )
+            .context("failed to prepare audit query")?;
+        let rows = stmt
+            .query_map(
+                params![actuator, since, until, limit, self.agent_id],
+                |row| {
+                    Ok((
+                        AuditEntry {
+                            session_id: row.get(0)?,
+                            turn_id: row.get(1)?,
+                            action_id: row.get(2)?,
+                            plugin: row.get(3)?,
+                            actuator: row.get(4)?,
+                            status: PlannedActionStatus::Planned,
+                            deny_reason: None,
+                            details: row.get(7)?,
+                            created_at: row.get(8)?,
+                        },
+                        row.get::<_, String>(5)?,
+                        row.get::<_, Option<String>>(6)?,
+                    ))
+                },
+            )
+            .context("failed to run audit query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to read audit rows")?;
+
+        rows.into_iter()
+            .map(|(mut entry, status, deny_reason)| {
+                entry.status = serde_json::from_value(Value::String(status))
+                    .context("audit row has an unknown status")?;
+                entry.deny_reason = deny_reason
+                    .map(|reason| serde_json::from_value(Value::String(reason)))
+                    .transpose()
+                    .context("audit row has an unknown deny reason")?;
+                Ok(entry)
+            })
+            .collect()
+    }
+
+    fn append_audit_entry(
+        &self,
+        session_id: &str,
+        turn_id: &str,
+        action: &PlannedAction,
+    ) -> anyhow::Result<()> {
+        if matches!(
+            action.status,
+            PlannedActionStatus::Planned | PlannedActionStatus::InProgress
+        ) {
+            return Ok(());
         }
 
-        let plugin = load_plugin_from_dir(&source_path)?;
-        if self
-            .builtin_plugins
-            .iter()
-            .any(|entry| entry.manifest.name == plugin.manifest.name)
-        {
+        let status =
+            serde_json::to_value(&action.status).context("failed to serialize audit status")?;
+        let deny_reason = action
+            .deny_reason
+            .map(serde_json::to_value)
+            .transpose()
+            .context("failed to serialize audit deny reason")?;
+        let conn = open_db(&self.db_path)?;
+        conn.execute(
+            "INSERT INTO action_audit
+                (id, agent_id, session_id, turn_id, action_id, plugin, actuator, status, deny_reason, details, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                next_id("audit"),
+                self.agent_id,
+                session_id,
+                turn_id,
+                action.action_id,
+                action.plugin,
+                action.actuator,
+                status.as_str(),
+                deny_reason.as_ref().and_then(Value::as_str),
+                action.details.as_deref().map(redact_secrets),
+                now_millis() as i64,
+            ],
+        )
+        .with_context(|| format!("failed to write audit entry for action {}", action.action_id))?;
+        Ok(())
+    }
+
+    pub fn export_events(
+        &self,
+        session_id: Option<&str>,
+        format: ExportFormat,
+        path: &Path,
+    ) -> anyhow::Result<usize> {
+        let conn = open_db(&self.db_path)?;
+        export_agent_events(&conn, &self.agent_id, session_id, format, path)
+    }
+
+    pub fn install_workspace_plugin(
+        &self,
+        workspace_dir: &str,
+        source: &str,
+    ) -> anyhow::Result<String> {
+        let (source_path, plugin) = load_plugin_source(source)?;
+        if let Some(error) = plugin.api_incompatibility() {
+            return Err(error.into());
+        }
+        if self.is_builtin_plugin(&plugin.manifest.name) {
             bail!(
                 "cannot install plugin '{}' because a builtin plugin already uses that name",
                 plugin.manifest.name
@@ -379,6 +1044,140 @@ impl PeasRuntime {
         ))
     }
 
+    pub fn validate_plugin_source(
+        &self,
+        workspace_dir: &str,
+        source: &str,
+    ) -> anyhow::Result<String> {
+        let (_, plugin) = load_plugin_source(source)?;
+        let name = plugin.manifest.name.as_str();
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+
+        if let Some(error) = plugin.api_incompatibility() {
+            errors.push(error.to_string());
+        }
+        if name.contains(char::is_whitespace) {
+            errors.push("plugin name must not contain spaces".to_string());
+        }
+        if self.is_builtin_plugin(name) {
+            errors.push(format!("a builtin plugin already uses the name '{name}'"));
+        }
+
+        let mut actuator_names = HashSet::new();
+        for actuator in &plugin.manifest.peas.actuators {
+            if !actuator_names.insert(actuator.name.as_str()) {
+                errors.push(format!("actuator '{}' is declared twice", actuator.name));
+            }
+            match plugin.actuator_executor(&actuator.name) {
+                "plugin_process" => {}
+                "native_filesystem"
+                    if NATIVE_FILESYSTEM_ACTUATORS.contains(&actuator.name.as_str()) => {}
+                "native_filesystem" => errors.push(format!(
+                    "actuator '{}' is not a native filesystem actuator ({})",
+                    actuator.name,
+                    NATIVE_FILESYSTEM_ACTUATORS.join(", ")
+                )),
+                other => errors.push(format!(
+                    "actuator '{}' uses unsupported executor '{other}'",
+                    actuator.name
+                )),
+            }
+            if let Some(root) = actuator.workspace_root.as_deref()
+                && let Err(error) = plugin.actuator_workspace_root(&actuator.name, workspace_dir)
+            {
+                warnings.push(format!(
+                    "actuator '{}' workspace_root '{root}' is not usable in this workspace: {error}",
+                    actuator.name
+                ));
+            }
+        }
+
+        if plugin.manifest.peas.performance.is_empty() {
+            warnings.push("no performance measures are defined".to_string());
+        }
+        if plugin.manifest.peas.actuators.is_empty() && plugin.manifest.peas.sensors.is_empty() {
+            warnings.push("plugin defines no actuators or sensors".to_string());
+        }
+        if let Some(timeout_ms) = plugin.manifest.limits.timeout_ms
+            && timeout_ms < MIN_PLUGIN_TIMEOUT_MS
+        {
+            warnings.push(format!(
+                "limits.timeout_ms {timeout_ms} is below the minimum and will be raised to {MIN_PLUGIN_TIMEOUT_MS}"
+            ));
+        }
+
+        if is_deno_available() {
+            let child = Command::new("deno")
+                .arg("check")
+                .arg("--quiet")
+                .arg(&plugin.entry_path)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .context("failed to run deno check")?;
+            match wait_for_plugin_process(
+                child,
+                "",
+                DENO_CHECK_TIMEOUT,
+                DEFAULT_PLUGIN_MAX_OUTPUT_BYTES,
+                &TurnCancel::default(),
+            )? {
+                PluginProcessExit::Completed(output) if !output.status.success() => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let (stderr, _) = truncate_text(stderr.trim(), 1_000);
+                    errors.push(format!(
+                        "deno check failed for {}: {stderr}",
+                        plugin.manifest.entry
+                    ));
+                }
+                PluginProcessExit::Completed(_) => {}
+                PluginProcessExit::TimedOut => errors.push(format!(
+                    "deno check for {} did not finish within {}s",
+                    plugin.manifest.entry,
+                    DENO_CHECK_TIMEOUT.as_secs()
+                )),
+                PluginProcessExit::OutputLimitExceeded => errors.push(format!(
+                    "deno check for {} produced too much output",
+                    plugin.manifest.entry
+                )),
+                PluginProcessExit::Cancelled => errors.push("deno check was cancelled".to_string()),
+            }
+        } else {
+            warnings
+                .push("deno runtime not found on PATH; the entry file was not checked".to_string());
+        }
+
+        if workspace_plugins_dir(workspace_dir).join(name).exists() {
+            warnings.push(format!(
+                "installing would replace the copy of '{name}' already in this workspace"
+            ));
+        }
+
+        let mut lines = Vec::new();
+        if errors.is_empty() {
+            lines.push(format!(
+                "plugin '{name}' v{} is valid; nothing was installed",
+                plugin.manifest.version
+            ));
+        } else {
+            lines.push(format!("plugin '{name}' failed validation:"));
+            lines.extend(errors.iter().map(|error| format!("- error: {error}")));
+        }
+        lines.extend(
+            warnings
+                .iter()
+                .map(|warning| format!("- warning: {warning}")),
+        );
+
+        if errors.is_empty() {
+            Ok(lines.join("\n"))
+        } else {
+            bail!("{}", lines.join("\n"))
+        }
+    }
+
     pub fn remove_workspace_plugin(
         &self,
         workspace_dir: &str,
@@ -389,11 +1188,7 @@ impl PeasRuntime {
             bail!("plugin name cannot be empty");
         }
 
-        if self
-            .builtin_plugins
-            .iter()
-            .any(|plugin| plugin.manifest.name == trimmed)
-        {
+        if self.is_builtin_plugin(trimmed) {
             bail!(
                 "cannot remove builtin plugin '{}'; disable it instead",
                 trimmed
@@ -404,12 +1199,18 @@ impl PeasRuntime {
         if !plugin_dir.exists() {
             bail!("workspace plugin '{}' not found", trimmed);
         }
+        self.ensure_chat_remains_without(workspace_dir, trimmed, "remove")?;
+        let dropped = self.drop_pending_approvals_for_plugin(workspace_dir, trimmed)?;
 
         fs::remove_dir_all(&plugin_dir)
             .with_context(|| format!("failed to remove {}", plugin_dir.display()))?;
         remove_workspace_plugin_registry_entry(workspace_dir, trimmed)?;
 
-        Ok(format!("removed plugin '{}'", trimmed))
+        Ok(format!(
+            "removed plugin '{}'{}",
+            trimmed,
+            format_dropped_approvals(dropped)
+        ))
     }
 
     pub fn set_workspace_plugin_enabled(
@@ -425,23 +1226,210 @@ impl PeasRuntime {
 
         let active = self.plugins_for_workspace(workspace_dir)?;
         let has_active = active.iter().any(|plugin| plugin.manifest.name == trimmed);
-        let has_builtin = self
-            .builtin_plugins
-            .iter()
-            .any(|plugin| plugin.manifest.name == trimmed);
+        let has_builtin = self.is_builtin_plugin(trimmed);
         let has_external = workspace_plugins_dir(workspace_dir).join(trimmed).exists();
 
         if !has_active && !has_builtin && !has_external {
-            bail!(
-                "plugin '{}' is not installed for workspace {}",
-                trimmed,
-                workspace_dir
-            );
+            return Err(PeasError::PluginNotInstalled {
+                plugin: trimmed.to_string(),
+                workspace_dir: workspace_dir.to_string(),
+            }
+            .into());
+        }
+
+        if enabled
+            && let Some((plugin, _, _)) = self
+                .plugins_with_registry(workspace_dir)?
+                .into_iter()
+                .find(|(plugin, _, _)| plugin.manifest.name == trimmed)
+            && let Some(error) = plugin.api_incompatibility()
+        {
+            return Err(error.into());
+        }
+
+        let mut dropped = 0;
+        if !enabled {
+            self.ensure_chat_remains_without(workspace_dir, trimmed, "disable")?;
+            dropped = self.drop_pending_approvals_for_plugin(workspace_dir, trimmed)?;
         }
 
         upsert_workspace_plugin_registry(workspace_dir, trimmed, enabled, None, None)?;
         let status = if enabled { "enabled" } else { "disabled" };
-        Ok(format!("plugin '{}' {status}", trimmed))
+        Ok(format!(
+            "plugin '{}' {status}{}",
+            trimmed,
+            format_dropped_approvals(dropped)
+        ))
+    }
+
+    pub fn plugin_overrides(&self, workspace_dir: &str) -> anyhow::Result<Vec<PluginOverride>> {
+        Ok(self
+            .plugins_with_registry(workspace_dir)?
+            .into_iter()
+            .map(|(plugin, enabled, _)| PluginOverride {
+                name: plugin.manifest.name,
+                enabled,
+            })
+            .collect())
+    }
+
+    pub fn apply_plugin_overrides(
+        &self,
+        workspace_dir: &str,
+        overrides: &[PluginOverride],
+    ) -> anyhow::Result<()> {
+        let known = self.plugins_with_registry(workspace_dir)?;
+        let override_for = |name: &str| {
+            overrides
+                .iter()
+                .find(|entry| entry.name.trim() == name)
+                .map(|entry| entry.enabled)
+        };
+
+        let unknown = overrides
+            .iter()
+            .map(|entry| entry.name.trim())
+            .filter(|name| {
+                !known
+                    .iter()
+                    .any(|(plugin, _, _)| plugin.manifest.name == *name)
+            })
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            bail!(
+                "plugins are not installed for workspace {}: {} (install them with /plugin add first)",
+                workspace_dir,
+                unknown.join(", ")
+            );
+        }
+
+        let chat_enabled = known.iter().any(|(plugin, enabled, _)| {
+            plugin.provides_chat() && override_for(&plugin.manifest.name).unwrap_or(*enabled)
+        });
+        if !chat_enabled {
+            bail!(
+                "import would leave no enabled plugin with the 'terminal_chat_percept' sensor and 'chat_effect_append' actuator"
+            );
+        }
+
+        let mut registry = load_workspace_plugin_registry_doc(workspace_dir)?;
+        for entry in overrides {
+            let name = entry.name.trim();
+            if let Some(existing) = registry
+                .plugins
+                .iter_mut()
+                .find(|existing| existing.name == name)
+            {
+                existing.enabled = entry.enabled;
+            } else {
+                registry.plugins.push(WorkspacePluginState {
+                    name: name.to_string(),
+                    enabled: entry.enabled,
+                    source: None,
+                    version: None,
+                    env: BTreeMap::new(),
+                });
+            }
+        }
+        registry.plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        save_workspace_plugin_registry_doc(workspace_dir, &registry)?;
+
+        for (plugin, enabled, _) in &known {
+            if *enabled && override_for(&plugin.manifest.name) == Some(false) {
+                self.drop_pending_approvals_for_plugin(workspace_dir, &plugin.manifest.name)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_workspace_plugin_env(
+        &self,
+        workspace_dir: &str,
+        plugin_name: &str,
+        key: &str,
+        value: Option<&str>,
+    ) -> anyhow::Result<String> {
+        let trimmed = plugin_name.trim();
+        if trimmed.is_empty() {
+            bail!("plugin name cannot be empty");
+        }
+        let key = key.trim();
+        if !is_valid_env_key(key) {
+            bail!("invalid environment variable name '{key}'");
+        }
+        if !self
+            .plugins_with_registry(workspace_dir)?
+            .iter()
+            .any(|(plugin, _, _)| plugin.manifest.name == trimmed)
+        {
+            return Err(PeasError::PluginNotInstalled {
+                plugin: trimmed.to_string(),
+                workspace_dir: workspace_dir.to_string(),
+            }
+            .into());
+        }
+
+        let mut registry = load_workspace_plugin_registry_doc(workspace_dir)?;
+        let index = match registry
+            .plugins
+            .iter()
+            .position(|entry| entry.name == trimmed)
+        {
+            Some(index) => index,
+            None => {
+                registry.plugins.push(WorkspacePluginState {
+                    name: trimmed.to_string(),
+                    enabled: true,
+                    source: None,
+                    version: None,
+                    env: BTreeMap::new(),
+                });
+                registry.plugins.len() - 1
+            }
+        };
+
+        let entry = &mut registry.plugins[index];
+        let message = match value {
+            Some(value) => {
+                register_secret(value);
+                entry.env.insert(key.to_string(), value.to_string());
+                format!("set {key} for plugin '{trimmed}'")
+            }
+            None => {
+                if entry.env.remove(key).is_none() {
+                    bail!("plugin '{}' has no environment variable {key}", trimmed);
+                }
+                format!("removed {key} from plugin '{trimmed}'")
+            }
+        };
+
+        registry.plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        save_workspace_plugin_registry_doc(workspace_dir, &registry)?;
+        Ok(message)
+    }
+
+    fn ensure_chat_remains_without(
+        &self,
+        workspace_dir: &str,
+        plugin_name: &str,
+        verb: &str,
+    ) -> anyhow::Result<()> {
+        let active = self.plugins_for_workspace(workspace_dir)?;
+        let provides_chat = active
+            .iter()
+            .any(|plugin| plugin.manifest.name == plugin_name && plugin.provides_chat());
+        let other_chat = active
+            .iter()
+            .any(|plugin| plugin.manifest.name != plugin_name && plugin.provides_chat());
+
+        if provides_chat && !other_chat {
+            return Err(PeasError::OnlyChatPlugin {
+                plugin: plugin_name.to_string(),
+                verb: verb.to_string(),
+            }
+            .into());
+        }
+        Ok(())
     }
 
     pub fn list_workspace_plugins(&self, workspace_dir: &str) -> anyhow::Result<String> {
@@ -450,15 +1438,136 @@ impl PeasRuntime {
             return Ok("no plugins available".to_string());
         }
 
+        let deno_available = is_deno_available();
         let mut lines = Vec::new();
         for (plugin, enabled, source) in all_plugins {
-            lines.push(format!(
-                "- {} v{} [{}] source={}",
+            let mut line = format!(
+                "- {} v{} [{}] source={} status={}",
                 plugin.manifest.name,
                 plugin.manifest.version,
                 if enabled { "enabled" } else { "disabled" },
-                source
-            ));
+                source,
+                plugin_status_message(&plugin, enabled, deno_available)
+            );
+            if !plugin.env.is_empty() {
+                let keys = plugin.env.keys().cloned().collect::<Vec<_>>();
+                line.push_str(&format!(" env={}", keys.join(",")));
+            }
+            let circuits = self.circuit_states(&plugin.manifest.name);
+            if !circuits.is_empty() {
+                line.push_str(&format!(" circuits={}", circuits.join(",")));
+            }
+            lines.push(line);
+        }
+
+        Ok(lines.join("\n"))
+    }
+
+    pub fn workspace_plugin_statuses(
+        &self,
+        workspace_dir: &str,
+    ) -> anyhow::Result<Vec<PluginStatus>> {
+        let deno_available = is_deno_available();
+        Ok(self
+            .plugins_with_registry(workspace_dir)?
+            .into_iter()
+            .map(|(plugin, enabled, source)| PluginStatus {
+                status_message: plugin_status_message(&plugin, enabled, deno_available),
+                name: plugin.manifest.name.clone(),
+                version: plugin.manifest.version.clone(),
+                source,
+                enabled,
+                actuators: plugin
+                    .manifest
+                    .peas
+                    .actuators
+                    .iter()
+                    .map(|component| component.name.clone())
+                    .collect(),
+                sensors: plugin
+                    .manifest
+                    .peas
+                    .sensors
+                    .iter()
+                    .map(|component| component.name.clone())
+                    .collect(),
+            })
+            .collect())
+    }
+
+    pub fn reload_plugins(&self, workspace_dir: &str) -> anyhow::Result<String> {
+        let reloaded = load_plugins(&builtin_plugins_dir())?;
+        if reloaded.is_empty() {
+            bail!("no PEAS plugins were loaded");
+        }
+
+        let workspace_plugins = load_plugins(&workspace_plugins_dir(workspace_dir))?;
+        let mut combined = reloaded.clone();
+        combined.extend(workspace_plugins.iter().cloned());
+        combined.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+        validate_unique_plugin_names(&combined)?;
+
+        let previous = self.builtin_plugins();
+        let mut changes = Vec::new();
+        for plugin in &reloaded {
+            let Some(before) = previous
+                .iter()
+                .find(|entry| entry.manifest.name == plugin.manifest.name)
+            else {
+                changes.push(format!("+ {} (added)", plugin.manifest.name));
+                continue;
+            };
+
+            let component_changes = [
+                describe_component_changes(
+                    "actuators",
+                    &before.manifest.peas.actuators,
+                    &plugin.manifest.peas.actuators,
+                ),
+                describe_component_changes(
+                    "sensors",
+                    &before.manifest.peas.sensors,
+                    &plugin.manifest.peas.sensors,
+                ),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+            if !component_changes.is_empty() {
+                changes.push(format!(
+                    "~ {}: {}",
+                    plugin.manifest.name,
+                    component_changes.join("; ")
+                ));
+            } else if before.manifest.version != plugin.manifest.version {
+                changes.push(format!(
+                    "~ {}: v{} -> v{}",
+                    plugin.manifest.name, before.manifest.version, plugin.manifest.version
+                ));
+            }
+        }
+        for plugin in &previous {
+            if !reloaded
+                .iter()
+                .any(|entry| entry.manifest.name == plugin.manifest.name)
+            {
+                changes.push(format!("- {} (removed)", plugin.manifest.name));
+            }
+        }
+
+        let builtin_count = reloaded.len();
+        if let Ok(mut guard) = self.builtin_plugins.lock() {
+            *guard = reloaded;
+        }
+
+        let mut lines = vec![format!(
+            "reloaded {builtin_count} builtin and {} workspace plugins",
+            workspace_plugins.len()
+        )];
+        if changes.is_empty() {
+            lines.push("no builtin plugin changes".to_string());
+        } else {
+            lines.extend(changes);
         }
 
         Ok(lines.join("\n"))
@@ -489,38 +1598,120 @@ impl PeasRuntime {
         Ok(lines.join("\n"))
     }
 
-    pub async fn stream_percept_effects(
+    pub async fn check_model(
         &self,
-        session_id: &str,
-        domain: &str,
-        percept: Percept,
-        workspace_dir: &str,
         provider_name: &str,
         model: &str,
         keys: &AgentKeys,
+    ) -> anyhow::Result<()> {
+        let provider_id = parse_provider_id(provider_name).ok_or_else(|| {
+            anyhow::anyhow!("unsupported provider '{provider_name}' for fiddlesticks facade")
+        })?;
+        let api_key = resolve_provider_api_key(keys, provider_name)?;
+        let provider = build_provider_from_api_key(provider_id, api_key)
+            .map_err(|error| anyhow::anyhow!("failed to build provider facade: {error}"))?;
+
+        let service = chat_service(provider);
+        let session = ChatSession::new(next_id("health"), provider_id, model.to_string());
+        let request =
+            ChatTurnRequest::new(session, "Reply with OK.".to_string()).enable_streaming();
+
+        let check = async {
+            let mut stream = service
+                .stream_turn(request)
+                .await
+                .map_err(|error| anyhow::anyhow!("model request failed to start: {error}"))?;
+            match stream.next().await {
+                Some(Err(error)) => Err(anyhow::anyhow!("model request failed: {error}")),
+                Some(Ok(_)) | None => Ok(()),
+            }
+        };
+
+        tokio::time::timeout(MODEL_HEALTH_TIMEOUT, check)
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "model did not respond within {} seconds",
+                    MODEL_HEALTH_TIMEOUT.as_secs()
+                )
+            })?
+    }
+
+    pub fn stream_percept_effects(
+        &self,
+        session_id: &str,
+        domain: &str,
+        percept: Percept,
+        config: &PersistedAgentConfig,
+        cancel: &TurnCancel,
     ) -> anyhow::Result<EffectStream> {
         if domain != CHAT_DOMAIN {
             bail!("unsupported domain: {domain}");
         }
 
-        let provider_name = provider_name.to_string();
-        let model = model.to_string();
-        let keys = keys.clone();
+        let provider_name = config.settings.provider.clone();
+        let model = config.settings.model.clone();
+        let keys = config.keys.clone();
         let session_id = session_id.to_string();
-        let workspace_dir = workspace_dir.to_string();
+        let workspace_dir = config.settings.workspace_dir.clone();
         let runtime = self.clone();
         let active_plugins = runtime.plugins_for_workspace(&workspace_dir)?;
 
-        let Percept::UserText { turn_id, text } = percept;
-        runtime.append_event(
-            &session_id,
-            Some(turn_id.as_str()),
-            "percept_user_text",
-            Some("user"),
-            &text,
-        )?;
+        let sensor_name = match &percept {
+            Percept::UserText { .. } => CHAT_PERCEPT_SENSOR,
+            Percept::ActionFeedback { .. } => ACTION_FEEDBACK_SENSOR,
+        };
+        let chat_plugin = runtime.chat_plugin(&active_plugins).ok();
+        let sensor = chat_plugin.and_then(|plugin| {
+            plugin
+                .manifest
+                .peas
+                .sensors
+                .iter()
+                .find(|sensor| sensor.name == sensor_name)
+        });
+        let transforms = sensor
+            .map(|sensor| sensor.transforms.clone())
+            .unwrap_or_default();
+        let sensor_soul = chat_plugin
+            .zip(sensor.and_then(|sensor| sensor.soul.as_deref()))
+            .and_then(|(plugin, soul)| {
+                contained_soul_path(Path::new(&workspace_dir), &plugin.root_dir, soul)
+            });
+        let (turn_id, text, feedback_depth) = match percept {
+            Percept::UserText { turn_id, text } => {
+                let text = apply_percept_transforms(&transforms, text)?;
+                runtime.append_event(
+                    &session_id,
+                    Some(turn_id.as_str()),
+                    "percept_user_text",
+                    Some("user"),
+                    &text,
+                )?;
+                (turn_id, text, 0)
+            }
+            Percept::ActionFeedback {
+                turn_id,
+                text,
+                depth,
+            } => {
+                let text = apply_percept_transforms(&transforms, text)?;
+                runtime.append_event(
+                    &session_id,
+                    Some(turn_id.as_str()),
+                    "percept_action_feedback",
+                    Some("system"),
+                    &text,
+                )?;
+                (turn_id, text, depth)
+            }
+        };
 
-        let pending = runtime.take_pending_approvals(&session_id);
+        let pending = if feedback_depth == 0 {
+            runtime.take_pending_approvals(&session_id)
+        } else {
+            Vec::new()
+        };
         if !pending.is_empty() {
             let Some(decision) = parse_approval_decision(&text, &pending) else {
                 runtime.set_pending_approvals(&session_id, pending.clone());
@@ -560,10 +1751,13 @@ impl PeasRuntime {
                                 &workspace_dir,
                                 &action,
                                 PermissionMode::AllowOneShot,
+                                cancel,
                             )? {
                                 Some(outcome) => {
                                     action.status = map_outcome_status(&outcome.status);
                                     action.details = Some(outcome.details.clone());
+                                    action.result = outcome.structured.clone();
+                                    action.deny_reason = outcome.deny_reason;
                                     effects.push(Effect::ActionStatusChanged {
                                         turn_id: turn_id.clone(),
                                         action,
@@ -575,6 +1769,7 @@ impl PeasRuntime {
                                     action.details = Some(
                                         "No executor available for approved action".to_string(),
                                     );
+                                    action.deny_reason = Some(DenyReason::NoExecutor);
                                     effects.push(Effect::ActionStatusChanged {
                                         turn_id: turn_id.clone(),
                                         action,
@@ -591,6 +1786,7 @@ impl PeasRuntime {
                             let mut action = entry.action;
                             action.status = PlannedActionStatus::Skipped;
                             action.details = Some("Action denied by user".to_string());
+                            action.deny_reason = Some(DenyReason::UserDenied);
                             effects.push(Effect::ActionStatusChanged {
                                 turn_id: turn_id.clone(),
                                 action,
@@ -629,22 +1825,42 @@ impl PeasRuntime {
         }
 
         let chat_plugin = runtime.chat_plugin(&active_plugins)?;
+        let plan_started = Instant::now();
         let plan = runtime.run_chat_plugin(
             chat_plugin,
             ChatPluginPerceptInput {
                 session_id: session_id.clone(),
                 turn_id: turn_id.clone(),
                 text: text.clone(),
+                sensor: if feedback_depth == 0 {
+                    CHAT_PERCEPT_SENSOR
+                } else {
+                    ACTION_FEEDBACK_SENSOR
+                }
+                .to_string(),
             },
+            cancel,
         )?;
 
-        if plan.mode != "stream_chat" {
+        if plan.mode != "stream_chat" && plan.mode != "direct_actions" {
             bail!("unsupported chat plugin mode: {}", plan.mode);
         }
+        let mut timing = TurnTiming {
+            plan_ms: elapsed_ms(plan_started),
+            ..TurnTiming::default()
+        };
 
         let mut prompt = plan.user_prompt.unwrap_or(text);
         let mut pre_effects = Vec::new();
         let mut sensor_notes = Vec::new();
+        let mut feedback_notes = Vec::new();
+        if feedback_depth > 0 {
+            pre_effects.push(Effect::TaskCompletion {
+                turn_id: turn_id.clone(),
+                status: "in_progress".to_string(),
+                details: format!("reacting to action feedback (depth {feedback_depth})"),
+            });
+        }
         let mut performance_tracker = PerformanceScoreTracker::new(&active_plugins);
         let mut planned_actions = runtime.materialize_planned_actions(&plan.planned_actions);
         if !planned_actions.is_empty() {
@@ -654,7 +1870,39 @@ impl PeasRuntime {
             });
         }
 
-        for action in &mut planned_actions {
+        for index in 0..planned_actions.len() {
+            if cancel.is_cancelled() {
+                bail!("turn {turn_id} was cancelled");
+            }
+
+            if let Some(note) =
+                runtime.apply_action_limit(&session_id, index, &mut planned_actions[index])
+            {
+                sensor_notes.push(note);
+                pre_effects.push(Effect::ActionStatusChanged {
+                    turn_id: turn_id.clone(),
+                    action: planned_actions[index].clone(),
+                });
+                continue;
+            }
+
+            if let Some(reason) = unmet_dependency(&plan.planned_actions, &planned_actions, index) {
+                let action = &mut planned_actions[index];
+                action.status = PlannedActionStatus::Skipped;
+                sensor_notes.push(format!(
+                    "sensor plan_dependency_skipped: actuator={} {reason}",
+                    action.actuator
+                ));
+                action.details = Some(reason);
+                action.deny_reason = Some(DenyReason::DependencyUnmet);
+                pre_effects.push(Effect::ActionStatusChanged {
+                    turn_id: turn_id.clone(),
+                    action: action.clone(),
+                });
+                continue;
+            }
+
+            let action = &mut planned_actions[index];
             action.status = PlannedActionStatus::InProgress;
             action.details = Some("Action started".to_string());
             pre_effects.push(Effect::ActionStatusChanged {
@@ -662,17 +1910,25 @@ impl PeasRuntime {
                 action: action.clone(),
             });
 
-            match runtime.execute_planned_action(
+            let action_started = Instant::now();
+            let outcome = runtime.execute_planned_action(
                 &active_plugins,
                 &workspace_dir,
                 action,
                 PermissionMode::Enforce,
-            )? {
+                cancel,
+            )?;
+            timing.action_ms.push(elapsed_ms(action_started));
+            match outcome {
                 Some(outcome) => {
                     sensor_notes.push(outcome.sensor_output.clone());
+                    if plan.planned_actions[index].feedback && outcome.status == "completed" {
+                        feedback_notes.push(outcome.sensor_output.clone());
+                    }
                     if let Some(plugin) = runtime.resolve_action_plugin(&active_plugins, action) {
                         performance_tracker.record(plugin, action, &outcome);
                     }
+                    action.deny_reason = outcome.deny_reason;
                     if outcome.status == "blocked" {
                         action.status = PlannedActionStatus::AwaitingApproval;
                         action.details = Some(outcome.details.clone());
@@ -686,6 +1942,7 @@ impl PeasRuntime {
                     } else {
                         action.status = map_outcome_status(&outcome.status);
                         action.details = Some(outcome.details.clone());
+                        action.result = outcome.structured.clone();
                     }
                     pre_effects.push(Effect::ActionStatusChanged {
                         turn_id: turn_id.clone(),
@@ -695,6 +1952,7 @@ impl PeasRuntime {
                 None => {
                     action.status = PlannedActionStatus::Skipped;
                     action.details = Some("No executor available for action".to_string());
+                    action.deny_reason = Some(DenyReason::NoExecutor);
                     pre_effects.push(Effect::ActionStatusChanged {
                         turn_id: turn_id.clone(),
                         action: action.clone(),
@@ -703,6 +1961,18 @@ impl PeasRuntime {
             }
         }
 
+        if !feedback_notes.is_empty() {
+            match feedback_percept(
+                &turn_id,
+                feedback_depth,
+                runtime.max_feedback_depth,
+                &feedback_notes,
+            ) {
+                Ok(feedback) => runtime.set_pending_feedback(&session_id, feedback),
+                Err(note) => sensor_notes.push(note),
+            }
+        }
+
         if !sensor_notes.is_empty() {
             prompt = format!(
                 "{prompt}\n\nPlugin sensor observations:\n{}\nUse these observations directly. If an action is blocked by permissions, ask the user for explicit per-action approval before requesting broader access.",
@@ -737,9 +2007,42 @@ impl PeasRuntime {
                     yield effect;
                 }
                 yield Effect::ChatResponse {
-                    turn_id: turn_id_for_stream,
+                    turn_id: turn_id_for_stream.clone(),
                     text: approval_prompt,
                 };
+                yield Effect::TurnTiming {
+                    turn_id: turn_id_for_stream,
+                    timing,
+                };
+            };
+            return Ok(Box::pin(stream));
+        }
+
+        if plan.mode == "direct_actions" {
+            let response = if sensor_notes.is_empty() {
+                "No actions were executed for this request.".to_string()
+            } else {
+                sensor_notes.join("\n\n")
+            };
+            let stream = try_stream! {
+                for effect in pre_effects {
+                    yield effect;
+                }
+                yield Effect::ChatResponse {
+                    turn_id: turn_id_for_stream.clone(),
+                    text: response,
+                };
+                if let Some(task_completion) = plan.task_completion {
+                    yield Effect::TaskCompletion {
+                        turn_id: turn_id_for_stream.clone(),
+                        status: task_completion.status,
+                        details: task_completion.details,
+                    };
+                }
+                yield Effect::TurnTiming {
+                    turn_id: turn_id_for_stream,
+                    timing,
+                };
             };
             return Ok(Box::pin(stream));
         }
@@ -749,15 +2052,11 @@ impl PeasRuntime {
                 yield effect;
             }
 
+            let model_started = Instant::now();
             let provider_id = parse_provider_id(&provider_name)
                 .ok_or_else(|| anyhow::anyhow!("unsupported provider '{provider_name}' for fiddlesticks facade"))?;
 
-            let api_key = keys
-                .api_keys
-                .iter()
-                .find(|entry| entry.provider.eq_ignore_ascii_case(&provider_name) && !entry.api_key.trim().is_empty())
-                .map(|entry| entry.api_key.clone())
-                .ok_or_else(|| anyhow::anyhow!("missing API key for provider '{provider_name}'"))?;
+            let api_key = resolve_provider_api_key(&keys, &provider_name)?;
 
             let provider = build_provider_from_api_key(provider_id, api_key)
                 .map_err(|error| anyhow::anyhow!("failed to build provider facade: {error}"))?;
@@ -765,7 +2064,12 @@ impl PeasRuntime {
             let service = chat_service(provider);
             let mut session = ChatSession::new(session_id.clone(), provider_id, model.clone());
             let full_system_prompt =
-                runtime.build_chat_system_prompt(&active_plugins, plan.system_prompt.clone(), &workspace_dir);
+                runtime.build_chat_system_prompt(
+                    &active_plugins,
+                    plan.system_prompt.clone(),
+                    &workspace_dir,
+                    sensor_soul.clone(),
+                );
             if !full_system_prompt.trim().is_empty() {
                 session = session.with_system_prompt(full_system_prompt);
             }
@@ -827,13 +2131,19 @@ impl PeasRuntime {
                 }
             }
 
+            timing.model_ms = Some(elapsed_ms(model_started));
+
             if let Some(task_completion) = plan.task_completion {
                 yield Effect::TaskCompletion {
-                    turn_id: turn_id_for_stream,
+                    turn_id: turn_id_for_stream.clone(),
                     status: task_completion.status,
                     details: task_completion.details,
                 };
             }
+            yield Effect::TurnTiming {
+                turn_id: turn_id_for_stream,
+                timing,
+            };
         };
 
         Ok(Box::pin(stream))
@@ -848,14 +2158,14 @@ impl PeasRuntime {
                 session_id,
                 Some(turn_id.as_str()),
                 "effect_chat_response_delta",
-                Some("assistant"),
+                Some(self.assistant_role()),
                 text_delta,
             ),
             Effect::ChatResponse { turn_id, text } => self.append_event(
                 session_id,
                 Some(turn_id.as_str()),
                 "effect_chat_response",
-                Some("assistant"),
+                Some(self.assistant_role()),
                 text,
             ),
             Effect::TaskCompletion {
@@ -868,7 +2178,7 @@ impl PeasRuntime {
                     session_id,
                     Some(turn_id.as_str()),
                     "effect_task_completion",
-                    Some("assistant"),
+                    Some(self.assistant_role()),
                     &payload,
                 )
             }
@@ -879,18 +2189,30 @@ impl PeasRuntime {
                     session_id,
                     Some(turn_id.as_str()),
                     "effect_plan_updated",
-                    Some("assistant"),
+                    Some(self.assistant_role()),
                     &payload,
                 )
             }
             Effect::ActionStatusChanged { turn_id, action } => {
                 let payload = serde_json::to_string(action)
                     .context("failed to serialize planned action update")?;
+                self.append_audit_entry(session_id, turn_id, action)?;
                 self.append_event(
                     session_id,
                     Some(turn_id.as_str()),
                     "effect_action_status_changed",
-                    Some("assistant"),
+                    Some(self.assistant_role()),
+                    &payload,
+                )
+            }
+            Effect::TurnTiming { turn_id, timing } => {
+                let payload =
+                    serde_json::to_string(timing).context("failed to serialize turn timing")?;
+                self.append_event(
+                    session_id,
+                    Some(turn_id.as_str()),
+                    "effect_turn_timing",
+                    Some("system"),
                     &payload,
                 )
             }
@@ -901,8 +2223,9 @@ impl PeasRuntime {
         &self,
         plugin: &LoadedPlugin,
         input: ChatPluginPerceptInput,
+        cancel: &TurnCancel,
     ) -> anyhow::Result<ChatPluginPerceptPlan> {
-        self.run_plugin_with_input(plugin, &input)
+        self.run_plugin_with_input(plugin, &input, cancel)
             .context("chat plugin returned invalid json plan payload")
     }
 
@@ -910,6 +2233,7 @@ impl PeasRuntime {
         &self,
         plugin: &LoadedPlugin,
         input: &TInput,
+        cancel: &TurnCancel,
     ) -> anyhow::Result<TOutput>
     where
         TInput: Serialize,
@@ -923,35 +2247,30 @@ impl PeasRuntime {
             );
         }
 
-        let mut cmd = Command::new("deno");
-        cmd.arg("run").arg("--quiet");
-        append_deno_permissions(&mut cmd, plugin);
-
-        let mut child = cmd
-            .arg(&plugin.entry_path)
-            .current_dir(&plugin.root_dir)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .with_context(|| {
-                format!("failed to start deno for plugin '{}'", plugin.manifest.name)
-            })?;
-
         let input_json = serde_json::to_string(input).context("serialize plugin percept input")?;
-        {
-            let stdin = child
-                .stdin
-                .as_mut()
-                .context("failed to open stdin for plugin")?;
-            stdin
-                .write_all(input_json.as_bytes())
-                .context("failed to write plugin percept input")?;
-        }
-
-        let output = child
-            .wait_with_output()
-            .context("failed to wait for plugin process")?;
+        let output = match self.run_plugin_process(plugin, &input_json, cancel)? {
+            PluginProcessExit::Completed(output) => output,
+            PluginProcessExit::TimedOut => {
+                return Err(PluginLimitError::TimedOut {
+                    plugin: plugin.manifest.name.clone(),
+                    timeout_ms: plugin.timeout_ms(),
+                }
+                .into());
+            }
+            PluginProcessExit::OutputLimitExceeded => {
+                return Err(PluginLimitError::OutputLimitExceeded {
+                    plugin: plugin.manifest.name.clone(),
+                    max_output_bytes: plugin.max_output_bytes(),
+                }
+                .into());
+            }
+            PluginProcessExit::Cancelled => {
+                bail!(
+                    "plugin '{}' was stopped because its turn was cancelled",
+                    plugin.manifest.name
+                );
+            }
+        };
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -970,28 +2289,44 @@ impl PeasRuntime {
         Ok(serde_json::from_str::<TOutput>(trimmed)?)
     }
 
-    fn chat_plugin<'a>(&self, plugins: &'a [LoadedPlugin]) -> anyhow::Result<&'a LoadedPlugin> {
-        plugins
-            .iter()
-            .find(|plugin| {
-                plugin
-                    .manifest
-                    .peas
-                    .sensors
-                    .iter()
-                    .any(|sensor| sensor.name == "terminal_chat_percept")
-                    && plugin
-                        .manifest
-                        .peas
-                        .actuators
-                        .iter()
-                        .any(|actuator| actuator.name == "chat_effect_append")
-            })
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "no active plugin can process chat percepts (missing sensor 'terminal_chat_percept' and actuator 'chat_effect_append')"
-                )
-            })
+    fn run_plugin_process(
+        &self,
+        plugin: &LoadedPlugin,
+        input_json: &str,
+        cancel: &TurnCancel,
+    ) -> anyhow::Result<PluginProcessExit> {
+        for value in plugin.env.values() {
+            register_secret(value);
+        }
+        let mut cmd = Command::new("deno");
+        cmd.arg("run").arg("--quiet");
+        append_deno_permissions(&mut cmd, plugin);
+
+        let child = cmd
+            .arg(&plugin.entry_path)
+            .current_dir(&plugin.root_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| {
+                format!("failed to start deno for plugin '{}'", plugin.manifest.name)
+            })?;
+
+        wait_for_plugin_process(
+            child,
+            input_json,
+            Duration::from_millis(plugin.timeout_ms()),
+            plugin.max_output_bytes(),
+            cancel,
+        )
+    }
+
+    fn chat_plugin<'a>(&self, plugins: &'a [LoadedPlugin]) -> anyhow::Result<&'a LoadedPlugin> {
+        plugins
+            .iter()
+            .find(|plugin| plugin.provides_chat())
+            .ok_or_else(|| PeasError::NoChatPlugin.into())
     }
 
     fn materialize_planned_actions(&self, specs: &[PlannedActionSpec]) -> Vec<PlannedAction> {
@@ -1005,6 +2340,8 @@ impl PeasRuntime {
                 args: spec.args.clone(),
                 status: PlannedActionStatus::Planned,
                 details: None,
+                result: None,
+                deny_reason: None,
             })
             .collect()
     }
@@ -1015,12 +2352,149 @@ impl PeasRuntime {
         workspace_dir: &str,
         action: &PlannedAction,
         permission_mode: PermissionMode,
+        cancel: &TurnCancel,
     ) -> anyhow::Result<Option<ActionOutcome>> {
         let Some(plugin) = self.resolve_action_plugin(plugins, action) else {
             return Ok(None);
         };
 
+        if let Some(schedule) = &self.schedule
+            && !schedule.allows(SystemTime::now())
+        {
+            return Ok(Some(ActionOutcome {
+                status: "skipped".to_string(),
+                details: format!(
+                    "actuator '{}' not run; outside the allowed action windows",
+                    action.actuator
+                ),
+                sensor_output: format!(
+                    "sensor action_schedule_closed: actuator={} not run because actions are paused outside the allowed windows ({ALLOWED_WINDOWS_ENV}). Tell the user it can run later.",
+                    action.actuator
+                ),
+                structured: None,
+                deny_reason: Some(DenyReason::OutsideAllowedWindow),
+            }));
+        }
+
+        let breaker_key = (plugin.manifest.name.clone(), action.actuator.clone());
+        if let Some(retry_in) = self.circuit_retry_in(&breaker_key) {
+            return Ok(Some(ActionOutcome {
+                status: "skipped".to_string(),
+                details: format!(
+                    "circuit open for actuator '{}' after repeated failures; retrying in {}s",
+                    action.actuator,
+                    retry_in.as_secs().max(1)
+                ),
+                sensor_output: format!(
+                    "sensor actuator_circuit_open: actuator={} skipped after repeated failures; retry in {}s",
+                    action.actuator,
+                    retry_in.as_secs().max(1)
+                ),
+                structured: None,
+                deny_reason: Some(DenyReason::CircuitOpen),
+            }));
+        }
+
+        let (outcome, backend_failed) = match self.dispatch_planned_action(
+            plugin,
+            workspace_dir,
+            action,
+            permission_mode,
+            cancel,
+        ) {
+            Ok(outcome) => {
+                let backend_failed = matches!(
+                    outcome.deny_reason,
+                    Some(DenyReason::TimedOut | DenyReason::OutputLimitExceeded)
+                );
+                (outcome, backend_failed)
+            }
+            Err(error) if cancel.is_cancelled() => return Err(error),
+            Err(error) => {
+                let error = redact_secrets(&format!("{error:#}"));
+                let outcome = ActionOutcome {
+                    status: "failed".to_string(),
+                    details: error.clone(),
+                    sensor_output: format!(
+                        "sensor plugin_command_error: plugin={} actuator={} {error}",
+                        plugin.manifest.name, action.actuator
+                    ),
+                    structured: None,
+                    deny_reason: None,
+                };
+                (outcome, true)
+            }
+        };
+        if outcome.status == "completed" {
+            self.record_actuator_success(&breaker_key);
+        } else if backend_failed && plugin.actuator_executor(&action.actuator) == "plugin_process" {
+            self.record_actuator_failure(&breaker_key);
+        }
+        Ok(Some(outcome))
+    }
+
+    fn dispatch_planned_action(
+        &self,
+        plugin: &LoadedPlugin,
+        workspace_dir: &str,
+        action: &PlannedAction,
+        permission_mode: PermissionMode,
+        cancel: &TurnCancel,
+    ) -> anyhow::Result<ActionOutcome> {
+        let actuator_root = match plugin.actuator_workspace_root(&action.actuator, workspace_dir) {
+            Ok(root) => root.to_string_lossy().to_string(),
+            Err(error) => {
+                return Ok(ActionOutcome {
+                    status: "failed".to_string(),
+                    details: error.to_string(),
+                    sensor_output: format!(
+                        "sensor plugin_command_error: plugin={} actuator={} {error}",
+                        plugin.manifest.name, action.actuator
+                    ),
+                    structured: None,
+                    deny_reason: None,
+                });
+            }
+        };
+
         let actuator_executor = plugin.actuator_executor(&action.actuator);
+        if matches!(permission_mode, PermissionMode::Enforce)
+            && !plugin.provides_chat()
+            && let Some(pattern) = matching_risk_pattern(&self.risk_patterns, &action.args)
+        {
+            return Ok(ActionOutcome {
+                status: "blocked".to_string(),
+                details: format!(
+                    "risk: high; arguments of actuator '{}' match destructive pattern '{pattern}'",
+                    action.actuator
+                ),
+                sensor_output: format!(
+                    "sensor actuator_high_risk: actuator={} pattern={pattern:?} awaiting approval. Tell the user what the action would destroy and ask for explicit per-action approval.",
+                    action.actuator
+                ),
+                structured: None,
+                deny_reason: Some(DenyReason::HighRisk),
+            });
+        }
+
+        if matches!(permission_mode, PermissionMode::Enforce)
+            && self.actuator_requires_approval(plugin, &action.actuator)
+        {
+            return Ok(ActionOutcome {
+                status: "blocked".to_string(),
+                details: format!(
+                    "actuator '{}' requires per-action approval",
+                    action.actuator
+                ),
+                sensor_output: format!(
+                    "sensor actuator_approval_required: actuator={} awaiting approval. Ask the user for explicit per-action approval.",
+                    action.actuator
+                ),
+                structured: None,
+                deny_reason: Some(DenyReason::ApprovalRequired),
+            });
+        }
+
         if actuator_executor == "native_filesystem" {
             let fs_action = FilesystemActionPlan {
                 actuator: action.actuator.clone(),
@@ -1050,20 +2524,36 @@ impl PeasRuntime {
                     .get("max_lines")
                     .and_then(Value::as_u64)
                     .map(|value| value as usize),
+                contents: action
+                    .args
+                    .get("contents")
+                    .and_then(Value::as_str)
+                    .map(ToString::to_string),
+                append: action
+                    .args
+                    .get("append")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                respect_gitignore: action
+                    .args
+                    .get("respect_gitignore")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
             };
 
-            return self
-                .execute_filesystem_action(plugin, workspace_dir, &fs_action, permission_mode)
-                .map(Some);
+            return self.execute_filesystem_action(
+                plugin,
+                &actuator_root,
+                &fs_action,
+                permission_mode,
+            );
         }
 
         if actuator_executor == "plugin_process" {
-            return self
-                .execute_plugin_actuator(plugin, workspace_dir, action)
-                .map(Some);
+            return self.execute_plugin_actuator(plugin, &actuator_root, action, cancel);
         }
 
-        Ok(Some(ActionOutcome {
+        Ok(ActionOutcome {
             status: "skipped".to_string(),
             details: format!(
                 "unsupported actuator executor '{}' for actuator '{}'",
@@ -1073,7 +2563,31 @@ impl PeasRuntime {
                 "sensor plugin_command_error: unsupported actuator executor '{}' for {}",
                 actuator_executor, action.actuator
             ),
-        }))
+            structured: None,
+            deny_reason: Some(DenyReason::UnsupportedActuator),
+        })
+    }
+
+    fn actuator_requires_approval(&self, plugin: &LoadedPlugin, actuator_name: &str) -> bool {
+        if let Some(required) = plugin
+            .manifest
+            .peas
+            .actuators
+            .iter()
+            .find(|component| component.name == actuator_name)
+            .and_then(|component| component.require_approval)
+        {
+            return required;
+        }
+
+        if plugin.provides_chat() {
+            return false;
+        }
+
+        self.approval_defaults
+            .get(plugin.actuator_executor(actuator_name))
+            .copied()
+            .unwrap_or(true)
     }
 
     fn resolve_action_plugin<'a>(
@@ -1129,18 +2643,56 @@ impl PeasRuntime {
         permission_mode: PermissionMode,
     ) -> anyhow::Result<ActionOutcome> {
         let workspace_root = PathBuf::from(workspace_dir);
-        let requested_path = if action.actuator == "filesystem_read" {
-            action
-                .file_path
-                .as_deref()
-                .or(action.path.as_deref())
-                .unwrap_or(action.pattern.as_str())
-        } else {
-            action.path.as_deref().unwrap_or(".")
-        };
+        let requested_path =
+            if action.actuator == "filesystem_read" || action.actuator == "filesystem_write" {
+                action
+                    .file_path
+                    .as_deref()
+                    .or(action.path.as_deref())
+                    .unwrap_or(action.pattern.as_str())
+            } else {
+                action.path.as_deref().unwrap_or(".")
+            };
         let target_dir = resolve_requested_path(&workspace_root, requested_path);
 
-        if matches!(permission_mode, PermissionMode::Enforce)
+        if action.actuator == "filesystem_write" {
+            if !is_allowed_write_path(plugin, &workspace_root, &target_dir) {
+                return Ok(ActionOutcome {
+                    status: "failed".to_string(),
+                    details: format!(
+                        "filesystem write refused for path '{}'; it is outside allowed write roots",
+                        target_dir.display()
+                    ),
+                    sensor_output: format!(
+                        "sensor filesystem_command_error: actuator={} refused; requested path '{}' is outside allowed write roots {:?}",
+                        action.actuator,
+                        target_dir.display(),
+                        plugin.permissions().write
+                    ),
+                    structured: None,
+                    deny_reason: Some(DenyReason::OutsideWriteRoots),
+                });
+            }
+
+            if matches!(permission_mode, PermissionMode::Enforce) {
+                return Ok(ActionOutcome {
+                    status: "blocked".to_string(),
+                    details: format!(
+                        "filesystem write to '{}' requires per-action approval",
+                        display_path(&workspace_root, &target_dir)
+                    ),
+                    sensor_output: format!(
+                        "sensor filesystem_command_error: actuator={} awaiting approval to {} {} bytes to '{}'. Ask the user for explicit per-action approval.",
+                        action.actuator,
+                        if action.append { "append" } else { "write" },
+                        action.contents.as_deref().unwrap_or_default().len(),
+                        target_dir.display()
+                    ),
+                    structured: None,
+                    deny_reason: Some(DenyReason::WriteNeedsApproval),
+                });
+            }
+        } else if matches!(permission_mode, PermissionMode::Enforce)
             && !is_allowed_read_path(plugin, &workspace_root, &target_dir)
         {
             let sensor_output = format!(
@@ -1156,21 +2708,33 @@ impl PeasRuntime {
                     target_dir.display()
                 ),
                 sensor_output,
+                structured: None,
+                deny_reason: Some(DenyReason::OutsideReadRoots),
             });
         }
         let limit = action.max_results.unwrap_or(200).clamp(1, 500);
 
-        let (stdout, stderr, details, outcome_status) = match action.actuator.as_str() {
+        let output = match action.actuator.as_str() {
             "filesystem_grep" => {
                 run_native_grep(&workspace_root, &target_dir, &action.pattern, limit)?
             }
-            "filesystem_glob" => {
-                run_native_glob(&workspace_root, &target_dir, &action.pattern, limit)?
-            }
+            "filesystem_glob" => run_native_glob(
+                &workspace_root,
+                &target_dir,
+                &action.pattern,
+                limit,
+                action.respect_gitignore,
+            )?,
             "filesystem_read" => {
                 let max_lines = action.max_lines.unwrap_or(250).clamp(1, 1000);
                 run_native_read(&workspace_root, &target_dir, max_lines)?
             }
+            "filesystem_write" => run_native_write(
+                &workspace_root,
+                &target_dir,
+                action.contents.as_deref().unwrap_or_default(),
+                action.append,
+            )?,
             other => {
                 return Ok(ActionOutcome {
                     status: "skipped".to_string(),
@@ -1178,12 +2742,14 @@ impl PeasRuntime {
                     sensor_output: format!(
                         "sensor filesystem_command_error: unsupported actuator '{other}'"
                     ),
+                    structured: None,
+                    deny_reason: Some(DenyReason::UnsupportedActuator),
                 });
             }
         };
 
-        let (stdout_capped, stdout_truncated) = truncate_text(&stdout, 12_000);
-        let (stderr_capped, stderr_truncated) = truncate_text(&stderr, 4_000);
+        let (stdout_capped, stdout_truncated) = truncate_text(&output.stdout, 12_000);
+        let (stderr_capped, stderr_truncated) = truncate_text(&output.stderr, 4_000);
 
         let mut sensor_output = format!(
             "sensor filesystem_command_output: actuator={} path={} pattern={}\nstdout:\n{}",
@@ -1206,9 +2772,11 @@ impl PeasRuntime {
         }
 
         Ok(ActionOutcome {
-            status: outcome_status,
-            details,
+            status: output.status,
+            details: output.details,
             sensor_output,
+            structured: output.structured,
+            deny_reason: None,
         })
     }
 
@@ -1217,8 +2785,9 @@ impl PeasRuntime {
         plugin: &LoadedPlugin,
         workspace_dir: &str,
         action: &PlannedAction,
+        cancel: &TurnCancel,
     ) -> anyhow::Result<ActionOutcome> {
-        let output = self.run_plugin_with_input::<PluginActuatorInput, PluginActuatorOutput>(
+        let result = self.run_plugin_with_input::<PluginActuatorInput, PluginActuatorOutput>(
             plugin,
             &PluginActuatorInput {
                 kind: "actuator_execute".to_string(),
@@ -1226,12 +2795,32 @@ impl PeasRuntime {
                 args: action.args.clone(),
                 workspace_dir: workspace_dir.to_string(),
             },
-        )?;
+            cancel,
+        );
+        let output = match result {
+            Ok(output) => output,
+            Err(error) => {
+                let Some(limit_error) = error.downcast_ref::<PluginLimitError>() else {
+                    return Err(error);
+                };
+                return Ok(ActionOutcome {
+                    status: "failed".to_string(),
+                    details: limit_error.to_string(),
+                    sensor_output: format!(
+                        "sensor plugin_command_error: plugin={} actuator={} {}",
+                        plugin.manifest.name, action.actuator, limit_error
+                    ),
+                    structured: None,
+                    deny_reason: Some(limit_error.deny_reason()),
+                });
+            }
+        };
 
         let PluginActuatorOutput {
             status,
             details,
             sensor_output,
+            structured,
         } = output;
 
         let details = if details.trim().is_empty() {
@@ -1254,6 +2843,8 @@ impl PeasRuntime {
             status,
             details,
             sensor_output,
+            structured,
+            deny_reason: None,
         })
     }
 
@@ -1262,6 +2853,7 @@ impl PeasRuntime {
         plugins: &[LoadedPlugin],
         plugin_system_prompt: Option<String>,
         workspace_dir: &str,
+        sensor_soul: Option<PathBuf>,
     ) -> String {
         let mut sections = Vec::new();
 
@@ -1271,6 +2863,12 @@ impl PeasRuntime {
             }
         }
 
+        if let Some(name) = self.display_name() {
+            sections.push(format!(
+                "This agent is named {name}. Refer to yourself as {name} rather than Looper, since other agents may share this conversation."
+            ));
+        }
+
         let context = self.build_component_context(plugins);
         if !context.is_empty() {
             sections.push(format!(
@@ -1283,13 +2881,44 @@ impl PeasRuntime {
             sections.push(performance_prompt);
         }
 
-        if let Some(soul) = load_soul_prompt(workspace_dir) {
+        let soul = sensor_soul
+            .and_then(|path| self.load_soul_prompt(path))
+            .or_else(|| self.load_soul_prompt(resolve_soul_path(workspace_dir)));
+        if let Some(soul) = soul {
             sections.push(soul);
         }
 
         sections.join("\n\n")
     }
 
+    fn load_soul_prompt(&self, soul_path: PathBuf) -> Option<String> {
+        let metadata = fs::metadata(&soul_path).ok()?;
+        let modified = metadata.modified().ok()?;
+        let len = metadata.len();
+
+        if let Ok(guard) = self.soul_cache.lock()
+            && let Some(cached) = guard.get(&soul_path)
+            && cached.modified == modified
+            && cached.len == len
+        {
+            return cached.prompt.clone();
+        }
+
+        let content = fs::read_to_string(&soul_path).ok()?;
+        let prompt = build_soul_prompt(&content);
+        if let Ok(mut guard) = self.soul_cache.lock() {
+            guard.insert(
+                soul_path,
+                CachedSoul {
+                    modified,
+                    len,
+                    prompt: prompt.clone(),
+                },
+            );
+        }
+        prompt
+    }
+
     fn build_component_context(&self, plugins: &[LoadedPlugin]) -> String {
         let mut lines = Vec::new();
         for plugin in plugins {
@@ -1349,7 +2978,13 @@ impl PeasRuntime {
         let all_plugins = self.plugins_with_registry(workspace_dir)?;
         Ok(all_plugins
             .into_iter()
-            .filter_map(|(plugin, enabled, _)| if enabled { Some(plugin) } else { None })
+            .filter_map(|(plugin, enabled, _)| {
+                if enabled && plugin.api_incompatibility().is_none() {
+                    Some(plugin)
+                } else {
+                    None
+                }
+            })
             .collect())
     }
 
@@ -1357,7 +2992,8 @@ impl PeasRuntime {
         &self,
         workspace_dir: &str,
     ) -> anyhow::Result<Vec<(LoadedPlugin, bool, String)>> {
-        let mut plugins = self.builtin_plugins.clone();
+        let builtin_plugins = self.builtin_plugins();
+        let mut plugins = builtin_plugins.clone();
 
         let external_root = workspace_plugins_dir(workspace_dir);
         if external_root.exists() {
@@ -1374,6 +3010,11 @@ impl PeasRuntime {
             .iter()
             .map(|entry| (entry.name.clone(), entry.enabled))
             .collect::<HashMap<_, _>>();
+        let env_map = registry_doc
+            .plugins
+            .iter()
+            .map(|entry| (entry.name.clone(), entry.env.clone()))
+            .collect::<HashMap<_, _>>();
         let source_map = registry_doc
             .plugins
             .into_iter()
@@ -1386,7 +3027,10 @@ impl PeasRuntime {
             .collect::<HashMap<_, _>>();
 
         let mut result = Vec::new();
-        for plugin in plugins {
+        for mut plugin in plugins {
+            if let Some(env) = env_map.get(&plugin.manifest.name) {
+                plugin.env = env.clone();
+            }
             let enabled = enabled_map
                 .get(&plugin.manifest.name)
                 .copied()
@@ -1395,8 +3039,7 @@ impl PeasRuntime {
                 .get(&plugin.manifest.name)
                 .cloned()
                 .unwrap_or_else(|| {
-                    if self
-                        .builtin_plugins
+                    if builtin_plugins
                         .iter()
                         .any(|builtin| builtin.manifest.name == plugin.manifest.name)
                     {
@@ -1438,6 +3081,164 @@ impl PeasRuntime {
         Ok(())
     }
 
+    fn builtin_plugins(&self) -> Vec<LoadedPlugin> {
+        let Ok(guard) = self.builtin_plugins.lock() else {
+            return Vec::new();
+        };
+        guard.clone()
+    }
+
+    fn is_builtin_plugin(&self, plugin_name: &str) -> bool {
+        self.builtin_plugins()
+            .iter()
+            .any(|plugin| plugin.manifest.name == plugin_name)
+    }
+
+    pub fn state_snapshot(&self) -> RuntimeSnapshot {
+        let pending_approvals = self
+            .pending_approvals
+            .lock()
+            .map(|guard| {
+                guard
+                    .iter()
+                    .map(|(session_id, pending)| (session_id.clone(), pending.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let pending_feedback = self
+            .pending_feedback
+            .lock()
+            .map(|guard| {
+                guard
+                    .iter()
+                    .map(|(session_id, percept)| (session_id.clone(), percept.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        RuntimeSnapshot {
+            pending_approvals,
+            pending_feedback,
+        }
+    }
+
+    pub fn restore_from_snapshot(&self, snapshot: RuntimeSnapshot) {
+        if let Ok(mut guard) = self.pending_approvals.lock() {
+            *guard = snapshot
+                .pending_approvals
+                .into_iter()
+                .filter(|(_, pending)| !pending.is_empty())
+                .collect();
+        }
+        if let Ok(mut guard) = self.pending_feedback.lock() {
+            *guard = snapshot.pending_feedback.into_iter().collect();
+        }
+    }
+
+    pub fn turn_watchdog(&self) -> Option<Duration> {
+        self.turn_watchdog
+    }
+
+    pub fn turn_watchdog_cancels(&self) -> bool {
+        self.turn_watchdog_cancels
+    }
+
+    pub fn display_name(&self) -> Option<&str> {
+        self.display_name.as_deref()
+    }
+
+    fn assistant_role(&self) -> &str {
+        self.display_name.as_deref().unwrap_or("assistant")
+    }
+
+    pub fn record_stalled_turn(&self) -> u64 {
+        self.stalled_turns.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub fn stalled_turns(&self) -> u64 {
+        self.stalled_turns.load(Ordering::Relaxed)
+    }
+
+    pub fn take_pending_feedback(&self, session_id: &str) -> Option<Percept> {
+        let Ok(mut guard) = self.pending_feedback.lock() else {
+            return None;
+        };
+        guard.remove(session_id)
+    }
+
+    fn set_pending_feedback(&self, session_id: &str, percept: Percept) {
+        if let Ok(mut guard) = self.pending_feedback.lock() {
+            guard.insert(session_id.to_string(), percept);
+        }
+    }
+
+    fn circuit_retry_in(&self, key: &(String, String)) -> Option<Duration> {
+        let guard = self.breakers.lock().ok()?;
+        let opened_at = guard.get(key)?.opened_at?;
+        self.breaker_config
+            .cooldown
+            .checked_sub(opened_at.elapsed())
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    fn record_actuator_success(&self, key: &(String, String)) {
+        if let Ok(mut guard) = self.breakers.lock() {
+            guard.remove(key);
+        }
+    }
+
+    fn record_actuator_failure(&self, key: &(String, String)) {
+        let Ok(mut guard) = self.breakers.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        let breaker = guard.entry(key.clone()).or_insert(CircuitBreaker {
+            consecutive_failures: 0,
+            first_failure_at: now,
+            opened_at: None,
+        });
+
+        if breaker.opened_at.is_some() {
+            breaker.opened_at = Some(now);
+            return;
+        }
+        if now.duration_since(breaker.first_failure_at) > self.breaker_config.window {
+            breaker.consecutive_failures = 0;
+            breaker.first_failure_at = now;
+        }
+        breaker.consecutive_failures += 1;
+        if breaker.consecutive_failures >= self.breaker_config.failures {
+            breaker.opened_at = Some(now);
+        }
+    }
+
+    fn circuit_states(&self, plugin_name: &str) -> Vec<String> {
+        let Ok(guard) = self.breakers.lock() else {
+            return Vec::new();
+        };
+        let mut states = guard
+            .iter()
+            .filter(|((plugin, _), _)| plugin == plugin_name)
+            .filter_map(|((_, actuator), breaker)| {
+                let opened_at = breaker.opened_at?;
+                Some(
+                    match self
+                        .breaker_config
+                        .cooldown
+                        .checked_sub(opened_at.elapsed())
+                    {
+                        Some(remaining) if !remaining.is_zero() => {
+                            format!("{actuator}:open({}s)", remaining.as_secs().max(1))
+                        }
+                        _ => format!("{actuator}:half_open"),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        states.sort();
+        states
+    }
+
     fn take_pending_approvals(&self, session_id: &str) -> Vec<PendingApproval> {
         let Ok(mut guard) = self.pending_approvals.lock() else {
             return Vec::new();
@@ -1455,6 +3256,72 @@ impl PeasRuntime {
         }
     }
 
+    fn drop_pending_approvals_for_plugin(
+        &self,
+        workspace_dir: &str,
+        plugin_name: &str,
+    ) -> anyhow::Result<usize> {
+        let active = self.plugins_for_workspace(workspace_dir)?;
+        let actuators = active
+            .iter()
+            .filter(|plugin| plugin.manifest.name == plugin_name)
+            .flat_map(|plugin| plugin.manifest.peas.actuators.iter())
+            .map(|actuator| actuator.name.as_str())
+            .collect::<HashSet<_>>();
+
+        let Ok(mut guard) = self.pending_approvals.lock() else {
+            return Ok(0);
+        };
+        let mut dropped = 0;
+        for pending in guard.values_mut() {
+            let before = pending.len();
+            pending.retain(|entry| {
+                let action = &entry.action;
+                let owned = action.plugin == plugin_name
+                    || (action.plugin == "auto" && actuators.contains(action.actuator.as_str()));
+                !owned
+            });
+            dropped += before - pending.len();
+        }
+        guard.retain(|_, pending| !pending.is_empty());
+        Ok(dropped)
+    }
+
+    fn apply_action_limit(
+        &self,
+        session_id: &str,
+        index: usize,
+        action: &mut PlannedAction,
+    ) -> Option<String> {
+        let max_per_turn = self.action_limit.max_per_turn?;
+        if index < max_per_turn {
+            return None;
+        }
+
+        action.deny_reason = Some(DenyReason::ActionLimit);
+        if self.action_limit.overflow == ActionOverflow::Defer {
+            let reason = format!("Deferred: turn action limit of {max_per_turn} reached");
+            action.status = PlannedActionStatus::AwaitingApproval;
+            action.details = Some(reason.clone());
+            self.push_pending_approval(
+                session_id,
+                PendingApproval {
+                    action: action.clone(),
+                    reason,
+                },
+            );
+        } else {
+            action.status = PlannedActionStatus::Skipped;
+            action.details = Some(format!(
+                "Dropped: turn action limit of {max_per_turn} reached"
+            ));
+        }
+        Some(format!(
+            "sensor action_limit_reached: actuator={} not run; turns are limited to {max_per_turn} actions",
+            action.actuator
+        ))
+    }
+
     fn push_pending_approval(&self, session_id: &str, pending: PendingApproval) {
         if let Ok(mut guard) = self.pending_approvals.lock() {
             guard
@@ -1493,6 +3360,7 @@ fn load_plugins(plugins_root: &Path) -> anyhow::Result<Vec<LoadedPlugin>> {
         if manifest.name.trim().is_empty() {
             bail!("plugin at {} has empty name", manifest_path.display());
         }
+        validate_sensor_transforms(&manifest)?;
 
         let entry_path = path.join(&manifest.entry);
         if !entry_path.exists() {
@@ -1508,6 +3376,7 @@ fn load_plugins(plugins_root: &Path) -> anyhow::Result<Vec<LoadedPlugin>> {
             root_dir: path,
             entry_path,
             manifest,
+            env: BTreeMap::new(),
         });
     }
 
@@ -1527,18 +3396,87 @@ fn load_plugins(plugins_root: &Path) -> anyhow::Result<Vec<LoadedPlugin>> {
     Ok(plugins)
 }
 
-fn validate_unique_plugin_names(plugins: &[LoadedPlugin]) -> anyhow::Result<()> {
-    for pair in plugins.windows(2) {
-        if pair[0].manifest.name == pair[1].manifest.name {
-            bail!(
-                "duplicate plugin id '{}' in {} and {}",
-                pair[0].manifest.name,
-                pair[0].manifest_path.display(),
-                pair[1].manifest_path.display()
-            );
-        }
+fn plugin_status_message(plugin: &LoadedPlugin, enabled: bool, deno_available: bool) -> String {
+    if !enabled {
+        return "disabled in workspace plugin registry".to_string();
     }
-    Ok(())
+    if let Some(error) = plugin.api_incompatibility() {
+        return format!("incompatible: {error}");
+    }
+    if !plugin.entry_path.exists() {
+        return format!("entry file missing at {}", plugin.entry_path.display());
+    }
+
+    let mut requires_deno = !plugin.manifest.peas.sensors.is_empty();
+    for actuator in &plugin.manifest.peas.actuators {
+        match plugin.actuator_executor(&actuator.name) {
+            "native_filesystem" => {}
+            "plugin_process" => requires_deno = true,
+            other => {
+                return format!(
+                    "actuator '{}' uses unsupported executor '{other}'",
+                    actuator.name
+                );
+            }
+        }
+    }
+    if requires_deno && !deno_available {
+        return "deno runtime not found on PATH".to_string();
+    }
+
+    "ready".to_string()
+}
+
+fn is_deno_available() -> bool {
+    static DENO_AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *DENO_AVAILABLE.get_or_init(|| {
+        Command::new("deno")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    })
+}
+
+fn describe_component_changes(
+    label: &str,
+    before: &[PluginComponent],
+    after: &[PluginComponent],
+) -> Option<String> {
+    let added = after
+        .iter()
+        .filter(|component| !before.iter().any(|entry| entry.name == component.name))
+        .map(|component| format!("+{}", component.name));
+    let removed = before
+        .iter()
+        .filter(|component| !after.iter().any(|entry| entry.name == component.name))
+        .map(|component| format!("-{}", component.name));
+    let diff = added.chain(removed).collect::<Vec<_>>();
+    if diff.is_empty() {
+        return None;
+    }
+
+    Some(format!("{label} {}", diff.join(" ")))
+}
+
+fn validate_unique_plugin_names(plugins: &[LoadedPlugin]) -> anyhow::Result<()> {
+    for pair in plugins.windows(2) {
+        if pair[0].manifest.name == pair[1].manifest.name {
+            bail!(
+                "duplicate plugin id '{}' in {} and {}",
+                pair[0].manifest.name,
+                pair[0].manifest_path.display(),
+                pair[1].manifest_path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn builtin_plugins_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins")
 }
 
 fn workspace_plugins_dir(workspace_dir: &str) -> PathBuf {
@@ -1580,7 +3518,7 @@ fn save_workspace_plugin_registry_doc(
     }
     let text = serde_json::to_string_pretty(registry)
         .context("failed to serialize workspace plugin registry")?;
-    fs::write(&registry_path, text)
+    write_file_atomic(&registry_path, text.as_bytes())
         .with_context(|| format!("failed to write {}", registry_path.display()))
 }
 
@@ -1610,6 +3548,7 @@ fn upsert_workspace_plugin_registry(
             enabled,
             source,
             version,
+            env: BTreeMap::new(),
         });
     }
 
@@ -1633,6 +3572,279 @@ fn remove_workspace_plugin_registry_entry(
     save_workspace_plugin_registry_doc(workspace_dir, &registry)
 }
 
+impl ActionSchedule {
+    fn allows(&self, now: SystemTime) -> bool {
+        let secs = now
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+        let local_minutes = secs / 60 + self.utc_offset_minutes;
+        let days = local_minutes.div_euclid(24 * 60);
+        let minute = local_minutes.rem_euclid(24 * 60) as u32;
+        let weekday = (days + 3).rem_euclid(7) as usize;
+        let previous = (weekday + 6) % 7;
+
+        self.windows.iter().any(|window| {
+            if window.start_minute < window.end_minute {
+                window.days[weekday] && minute >= window.start_minute && minute < window.end_minute
+            } else {
+                (window.days[weekday] && minute >= window.start_minute)
+                    || (window.days[previous] && minute < window.end_minute)
+            }
+        })
+    }
+}
+
+fn approval_defaults_from_env() -> anyhow::Result<BTreeMap<String, bool>> {
+    parse_approval_defaults(env::var(ACTUATOR_APPROVAL_ENV).ok().as_deref())
+}
+
+fn parse_approval_defaults(raw: Option<&str>) -> anyhow::Result<BTreeMap<String, bool>> {
+    let mut defaults = DEFAULT_ACTUATOR_APPROVAL
+        .iter()
+        .map(|(executor, required)| (executor.to_string(), *required))
+        .collect::<BTreeMap<_, _>>();
+    let Some(raw) = raw else {
+        return Ok(defaults);
+    };
+
+    for entry in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        let Some((executor, value)) = entry.split_once('=') else {
+            bail!(
+                "invalid {ACTUATOR_APPROVAL_ENV} entry '{entry}'; expected <executor>=<required|open>"
+            );
+        };
+        let executor = executor.trim();
+        if !defaults.contains_key(executor) {
+            bail!("invalid {ACTUATOR_APPROVAL_ENV} entry '{entry}'; unknown executor '{executor}'");
+        }
+        let required = match value.trim().to_ascii_lowercase().as_str() {
+            "required" => true,
+            "open" => false,
+            other => bail!(
+                "invalid {ACTUATOR_APPROVAL_ENV} entry '{entry}'; expected 'required' or 'open', got '{other}'"
+            ),
+        };
+        defaults.insert(executor.to_string(), required);
+    }
+
+    Ok(defaults)
+}
+
+fn risk_patterns_from_env() -> Vec<String> {
+    let Ok(raw) = env::var(RISK_PATTERNS_ENV) else {
+        return DEFAULT_RISK_PATTERNS
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+    };
+    if raw.trim().eq_ignore_ascii_case("off") {
+        return Vec::new();
+    }
+
+    raw.split(',')
+        .map(|pattern| pattern.split_whitespace().collect::<Vec<_>>().join(" "))
+        .map(|pattern| pattern.to_ascii_lowercase())
+        .filter(|pattern| !pattern.is_empty())
+        .collect()
+}
+
+fn matching_risk_pattern<'a>(patterns: &'a [String], args: &Value) -> Option<&'a str> {
+    let mut texts = Vec::new();
+    collect_arg_strings(args, &mut texts);
+    let texts = texts
+        .iter()
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .map(|text| text.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    patterns
+        .iter()
+        .find(|pattern| texts.iter().any(|text| text.contains(pattern.as_str())))
+        .map(String::as_str)
+}
+
+fn collect_arg_strings<'a>(value: &'a Value, texts: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) => texts.push(text),
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_arg_strings(item, texts)),
+        Value::Object(fields) => fields
+            .values()
+            .for_each(|field| collect_arg_strings(field, texts)),
+        _ => {}
+    }
+}
+
+fn schedule_from_env() -> anyhow::Result<Option<ActionSchedule>> {
+    let Ok(raw) = env::var(ALLOWED_WINDOWS_ENV) else {
+        return Ok(None);
+    };
+    if raw.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let windows = raw
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(parse_time_window)
+        .collect::<anyhow::Result<Vec<_>>>()
+        .with_context(|| format!("invalid {ALLOWED_WINDOWS_ENV} value '{raw}'"))?;
+    let utc_offset_minutes = match env::var(UTC_OFFSET_ENV) {
+        Ok(offset) if !offset.trim().is_empty() => parse_utc_offset(offset.trim())
+            .with_context(|| format!("invalid {UTC_OFFSET_ENV} value '{offset}'"))?,
+        _ => 0,
+    };
+
+    Ok(Some(ActionSchedule {
+        windows,
+        utc_offset_minutes,
+    }))
+}
+
+fn parse_time_window(entry: &str) -> anyhow::Result<TimeWindow> {
+    let Some((day_spec, time_spec)) = entry.split_once(char::is_whitespace) else {
+        bail!("window '{entry}' must look like 'mon-fri 09:00-17:00'");
+    };
+
+    let mut days = [false; 7];
+    for part in day_spec.to_ascii_lowercase().split(',') {
+        if part == "daily" || part == "*" {
+            days = [true; 7];
+            continue;
+        }
+        let (first, last) = part.split_once('-').unwrap_or((part, part));
+        let Some(first) = WEEKDAYS.iter().position(|day| *day == first) else {
+            bail!("unknown weekday '{first}' in window '{entry}'");
+        };
+        let Some(last) = WEEKDAYS.iter().position(|day| *day == last) else {
+            bail!("unknown weekday '{last}' in window '{entry}'");
+        };
+        let mut day = first;
+        loop {
+            days[day] = true;
+            if day == last {
+                break;
+            }
+            day = (day + 1) % 7;
+        }
+    }
+
+    let Some((start, end)) = time_spec.trim().split_once('-') else {
+        bail!("window '{entry}' is missing a time range like 09:00-17:00");
+    };
+    let start_minute = parse_clock_minute(start)?;
+    let end_minute = parse_clock_minute(end)?;
+    if start_minute == end_minute {
+        bail!("window '{entry}' has the same start and end time");
+    }
+
+    Ok(TimeWindow {
+        days,
+        start_minute,
+        end_minute,
+    })
+}
+
+fn parse_clock_minute(raw: &str) -> anyhow::Result<u32> {
+    let raw = raw.trim();
+    let parsed = raw.split_once(':').and_then(|(hours, minutes)| {
+        Some((hours.parse::<u32>().ok()?, minutes.parse::<u32>().ok()?))
+    });
+    match parsed {
+        Some((24, 0)) => Ok(24 * 60),
+        Some((hours, minutes)) if hours < 24 && minutes < 60 => Ok(hours * 60 + minutes),
+        _ => bail!("invalid time '{raw}'; expected HH:MM"),
+    }
+}
+
+fn parse_utc_offset(raw: &str) -> anyhow::Result<i64> {
+    let (sign, rest) = match raw.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, raw.strip_prefix('+').unwrap_or(raw)),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours = hours.parse::<i64>().ok().filter(|hours| *hours <= 14);
+    let minutes = minutes.parse::<i64>().ok().filter(|minutes| *minutes < 60);
+    match (hours, minutes) {
+        (Some(hours), Some(minutes)) => Ok(sign * (hours * 60 + minutes)),
+        _ => bail!("expected an offset like +02:00 or -05:30"),
+    }
+}
+
+fn apply_percept_transforms(
+    transforms: &[PerceptTransform],
+    text: String,
+) -> anyhow::Result<String> {
+    let ansi = transforms
+        .iter()
+        .any(|transform| matches!(transform, PerceptTransform::StripAnsi))
+        .then(|| Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-_]"))
+        .transpose()
+        .context("failed to compile ANSI escape pattern")?;
+
+    let mut text = text;
+    for transform in transforms {
+        text = match transform {
+            PerceptTransform::StripAnsi => match &ansi {
+                Some(ansi) => ansi.replace_all(&text, "").into_owned(),
+                None => text,
+            },
+            PerceptTransform::Trim => text.trim().to_string(),
+            PerceptTransform::Lowercase => text.to_lowercase(),
+            PerceptTransform::Regex { find, replace } => Regex::new(find)
+                .with_context(|| format!("invalid percept transform pattern '{find}'"))?
+                .replace_all(&text, replace.as_str())
+                .into_owned(),
+            PerceptTransform::Truncate { max_chars } => text.chars().take(*max_chars).collect(),
+        };
+    }
+    Ok(text)
+}
+
+fn validate_sensor_transforms(manifest: &PluginManifest) -> anyhow::Result<()> {
+    for sensor in &manifest.peas.sensors {
+        for transform in &sensor.transforms {
+            if let PerceptTransform::Regex { find, .. } = transform {
+                Regex::new(find).with_context(|| {
+                    format!(
+                        "plugin '{}' sensor '{}' has an invalid transform pattern '{find}'",
+                        manifest.name, sensor.name
+                    )
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load_plugin_source(source: &str) -> anyhow::Result<(PathBuf, LoadedPlugin)> {
+    let source_path = PathBuf::from(source.trim());
+    if source_path.as_os_str().is_empty() {
+        bail!("plugin source path cannot be empty");
+    }
+    if !source_path.exists() {
+        bail!(
+            "plugin source path does not exist: {}",
+            source_path.display()
+        );
+    }
+    if !source_path.is_dir() {
+        bail!(
+            "plugin source must be a directory containing looper-plugin.json: {}",
+            source_path.display()
+        );
+    }
+
+    let plugin = load_plugin_from_dir(&source_path)?;
+    Ok((source_path, plugin))
+}
+
 fn load_plugin_from_dir(path: &Path) -> anyhow::Result<LoadedPlugin> {
     let manifest_path = path.join("looper-plugin.json");
     if !manifest_path.exists() {
@@ -1651,6 +3863,8 @@ fn load_plugin_from_dir(path: &Path) -> anyhow::Result<LoadedPlugin> {
         bail!("plugin at {} has empty name", manifest_path.display());
     }
 
+    validate_sensor_transforms(&manifest)?;
+
     let entry_path = path.join(&manifest.entry);
     if !entry_path.exists() {
         bail!(
@@ -1665,6 +3879,7 @@ fn load_plugin_from_dir(path: &Path) -> anyhow::Result<LoadedPlugin> {
         root_dir: path.to_path_buf(),
         entry_path,
         manifest,
+        env: BTreeMap::new(),
     })
 }
 
@@ -1694,7 +3909,125 @@ fn copy_dir_recursive(source: &Path, destination: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn wait_for_plugin_process(
+    mut child: Child,
+    input_json: &str,
+    timeout: Duration,
+    max_output_bytes: usize,
+    cancel: &TurnCancel,
+) -> anyhow::Result<PluginProcessExit> {
+    let deadline = Instant::now() + timeout;
+    let mut stdin = child
+        .stdin
+        .take()
+        .context("failed to open stdin for plugin")?;
+    let input = input_json.as_bytes().to_vec();
+    thread::spawn(move || {
+        let _ = stdin.write_all(&input);
+    });
+
+    let stdout_overflow = Arc::new(AtomicBool::new(false));
+    let stdout_reader = spawn_capped_reader(
+        child
+            .stdout
+            .take()
+            .context("failed to open stdout for plugin")?,
+        max_output_bytes,
+        Arc::clone(&stdout_overflow),
+    );
+    let stderr_reader = spawn_capped_reader(
+        child
+            .stderr
+            .take()
+            .context("failed to open stderr for plugin")?,
+        max_output_bytes,
+        Arc::new(AtomicBool::new(false)),
+    );
+
+    let stop = |mut child: Child, exit: PluginProcessExit| {
+        child.kill().ok();
+        child.wait().ok();
+        stdout_reader.recv_timeout(PLUGIN_READER_GRACE).ok();
+        stderr_reader.recv_timeout(PLUGIN_READER_GRACE).ok();
+        Ok(exit)
+    };
+
+    let status = loop {
+        if stdout_overflow.load(Ordering::Relaxed) {
+            return stop(child, PluginProcessExit::OutputLimitExceeded);
+        }
+
+        if let Some(status) = child
+            .try_wait()
+            .context("failed to wait for plugin process")?
+        {
+            break status;
+        }
+
+        if cancel.is_cancelled() {
+            return stop(child, PluginProcessExit::Cancelled);
+        }
+
+        if Instant::now() >= deadline {
+            return stop(child, PluginProcessExit::TimedOut);
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    let (Ok(stdout), Ok(stderr)) = (
+        stdout_reader.recv_timeout(remaining),
+        stderr_reader.recv_timeout(PLUGIN_READER_GRACE),
+    ) else {
+        return Ok(PluginProcessExit::TimedOut);
+    };
+    if stdout_overflow.load(Ordering::Relaxed) {
+        return Ok(PluginProcessExit::OutputLimitExceeded);
+    }
+
+    Ok(PluginProcessExit::Completed(Output {
+        status,
+        stdout,
+        stderr,
+    }))
+}
+
+fn spawn_capped_reader<R>(
+    mut reader: R,
+    max_bytes: usize,
+    overflowed: Arc<AtomicBool>,
+) -> mpsc::Receiver<Vec<u8>>
+where
+    R: Read + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut captured = Vec::new();
+        let mut chunk = [0u8; 8192];
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+
+            let remaining = max_bytes.saturating_sub(captured.len());
+            captured.extend_from_slice(&chunk[..read.min(remaining)]);
+            if read > remaining {
+                overflowed.store(true, Ordering::Relaxed);
+            }
+        }
+        let _ = sender.send(captured);
+    });
+    receiver
+}
+
 fn append_deno_permissions(cmd: &mut Command, plugin: &LoadedPlugin) {
+    if !plugin.env.is_empty() {
+        let keys = plugin.env.keys().cloned().collect::<Vec<_>>();
+        cmd.arg(format!("--allow-env={}", keys.join(",")));
+        cmd.envs(&plugin.env);
+    }
     append_deno_permission(
         cmd,
         "--allow-read",
@@ -1707,6 +4040,31 @@ fn append_deno_permissions(cmd: &mut Command, plugin: &LoadedPlugin) {
         &plugin.permissions().run,
         &plugin.root_dir,
     );
+    append_deno_permission(
+        cmd,
+        "--allow-write",
+        &plugin.permissions().write,
+        &plugin.root_dir,
+    );
+
+    let hosts = plugin
+        .permissions()
+        .net
+        .iter()
+        .map(|host| host.trim())
+        .filter(|host| !host.is_empty())
+        .collect::<Vec<_>>();
+    if hosts.contains(&"*") {
+        cmd.arg("--allow-net");
+    } else if !hosts.is_empty() {
+        cmd.arg(format!("--allow-net={}", hosts.join(",")));
+    }
+}
+
+fn is_valid_env_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(first) if first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
 }
 
 fn append_deno_permission(cmd: &mut Command, flag: &str, values: &[String], plugin_root: &Path) {
@@ -1743,10 +4101,92 @@ fn append_deno_permission(cmd: &mut Command, flag: &str, values: &[String], plug
 }
 
 impl LoadedPlugin {
+    fn api_incompatibility(&self) -> Option<PeasError> {
+        let required = self.manifest.api_version.unwrap_or(1);
+        if required == 0 || required > PLUGIN_API_VERSION {
+            return Some(PeasError::IncompatiblePlugin {
+                plugin: self.manifest.name.clone(),
+                required,
+            });
+        }
+        None
+    }
+
+    fn provides_chat(&self) -> bool {
+        self.manifest
+            .peas
+            .sensors
+            .iter()
+            .any(|sensor| sensor.name == CHAT_PERCEPT_SENSOR)
+            && self
+                .manifest
+                .peas
+                .actuators
+                .iter()
+                .any(|actuator| actuator.name == "chat_effect_append")
+    }
+
     fn permissions(&self) -> &PluginPermissions {
         &self.manifest.permissions
     }
 
+    fn timeout_ms(&self) -> u64 {
+        self.manifest
+            .limits
+            .timeout_ms
+            .unwrap_or(DEFAULT_PLUGIN_TIMEOUT_MS)
+            .max(MIN_PLUGIN_TIMEOUT_MS)
+    }
+
+    fn max_output_bytes(&self) -> usize {
+        self.manifest
+            .limits
+            .max_output_bytes
+            .unwrap_or(DEFAULT_PLUGIN_MAX_OUTPUT_BYTES)
+            .max(1)
+    }
+
+    fn actuator_workspace_root(
+        &self,
+        actuator_name: &str,
+        workspace_dir: &str,
+    ) -> anyhow::Result<PathBuf> {
+        let workspace_root = PathBuf::from(workspace_dir);
+        let Some(root) = self
+            .manifest
+            .peas
+            .actuators
+            .iter()
+            .find(|component| component.name == actuator_name)
+            .and_then(|component| component.workspace_root.as_deref())
+            .filter(|root| !root.trim().is_empty())
+        else {
+            return Ok(workspace_root);
+        };
+
+        let resolved = resolve_requested_path(&workspace_root, root);
+        let resolved_abs = canonicalize_for_check(&resolved);
+        let workspace_abs = canonicalize_for_check(&workspace_root);
+        if !resolved_abs.starts_with(&workspace_abs)
+            && !is_allowed_read_path(self, &workspace_root, &resolved)
+        {
+            bail!(
+                "actuator '{}' workspace_root '{}' is outside the workspace and not allowed by plugin read permissions",
+                actuator_name,
+                resolved.display()
+            );
+        }
+        if !resolved_abs.is_dir() {
+            bail!(
+                "actuator '{}' workspace_root '{}' is not a directory",
+                actuator_name,
+                resolved.display()
+            );
+        }
+
+        Ok(resolved_abs)
+    }
+
     fn actuator_executor(&self, actuator_name: &str) -> &str {
         if let Some(component_executor) = self
             .manifest
@@ -2003,7 +4443,7 @@ fn parse_action_ids(raw: &str, pending_ids: &HashSet<String>) -> HashSet<String>
 
 fn format_pending_approval_prompt(pending: &[PendingApproval]) -> String {
     let mut lines = vec![
-        "I need your approval to continue with actions outside current plugin read permissions."
+        "I need your approval to continue with actions that are outside current plugin read permissions or that write files."
             .to_string(),
         "Approve one or more actions with `approve <action_id>` (or `approve all`).".to_string(),
         "Deny with `deny <action_id>` (or `deny all`).".to_string(),
@@ -2021,9 +4461,40 @@ fn format_pending_approval_prompt(pending: &[PendingApproval]) -> String {
     lines.join("\n")
 }
 
-fn load_soul_prompt(workspace_dir: &str) -> Option<String> {
-    let soul_path = Path::new(workspace_dir).join("SOUL.md");
-    let content = fs::read_to_string(&soul_path).ok()?;
+fn resolve_soul_path(workspace_dir: &str) -> PathBuf {
+    soul_path_with_override(
+        Path::new(workspace_dir),
+        env::var(SOUL_PATH_ENV).ok().as_deref(),
+    )
+}
+
+fn soul_path_with_override(workspace_root: &Path, override_path: Option<&str>) -> PathBuf {
+    match override_path {
+        Some(value) if !value.trim().is_empty() => resolve_requested_path(workspace_root, value),
+        _ => workspace_root.join("SOUL.md"),
+    }
+}
+
+fn contained_soul_path(workspace_root: &Path, plugin_root: &Path, soul: &str) -> Option<PathBuf> {
+    if soul.trim().is_empty() {
+        return None;
+    }
+    let path = resolve_requested_path(workspace_root, soul);
+    let resolved = canonicalize_for_check(&path);
+    if [workspace_root, plugin_root]
+        .iter()
+        .any(|root| resolved.starts_with(canonicalize_for_check(root)))
+    {
+        return Some(resolved);
+    }
+    eprintln!(
+        "ignoring sensor soul {}: it is outside the workspace and the plugin directory",
+        path.display()
+    );
+    None
+}
+
+fn build_soul_prompt(content: &str) -> Option<String> {
     let trimmed = content.trim();
     if trimmed.is_empty() {
         return None;
@@ -2050,15 +4521,36 @@ fn resolve_requested_path(workspace_root: &Path, requested_path: &str) -> PathBu
 }
 
 fn is_allowed_read_path(plugin: &LoadedPlugin, workspace_root: &Path, target_path: &Path) -> bool {
-    let read = &plugin.permissions().read;
-    if read.iter().any(|entry| entry.trim() == ".") {
-        let target_abs = canonicalize_for_check(target_path);
+    is_within_permission_roots(
+        &plugin.permissions().read,
+        workspace_root,
+        &canonicalize_for_check(target_path),
+    )
+}
+
+fn is_allowed_write_path(plugin: &LoadedPlugin, workspace_root: &Path, target_path: &Path) -> bool {
+    if target_path
+        .components()
+        .any(|component| component == Component::ParentDir)
+    {
+        return false;
+    }
+
+    is_within_permission_roots(
+        &plugin.permissions().write,
+        workspace_root,
+        &canonicalize_for_check(target_path),
+    )
+}
+
+fn is_within_permission_roots(roots: &[String], workspace_root: &Path, target_abs: &Path) -> bool {
+    if roots.iter().any(|entry| entry.trim() == ".") {
         let workspace_abs = canonicalize_for_check(workspace_root);
         return target_abs.starts_with(workspace_abs);
     }
 
-    let target_abs = canonicalize_for_check(target_path);
-    read.iter()
+    roots
+        .iter()
         .map(|entry| entry.trim())
         .filter(|entry| !entry.is_empty())
         .map(|entry| {
@@ -2073,48 +4565,163 @@ fn is_allowed_read_path(plugin: &LoadedPlugin, workspace_root: &Path, target_pat
 }
 
 fn canonicalize_for_check(path: &Path) -> PathBuf {
-    if let Ok(abs) = fs::canonicalize(path) {
-        return abs;
-    }
+    let mut missing = Vec::new();
+    let mut current = path;
+    loop {
+        if let Ok(abs) = fs::canonicalize(current) {
+            return missing
+                .into_iter()
+                .rev()
+                .fold(abs, |mut resolved: PathBuf, component| {
+                    match component {
+                        Component::ParentDir => {
+                            resolved.pop();
+                        }
+                        Component::CurDir => {}
+                        other => resolved.push(other),
+                    }
+                    resolved
+                });
+        }
 
-    if let Some(parent) = path.parent()
-        && let Ok(parent_abs) = fs::canonicalize(parent)
-    {
-        if let Some(name) = path.file_name() {
-            return parent_abs.join(name);
+        match (current.parent(), current.components().next_back()) {
+            (Some(parent), Some(component)) => {
+                missing.push(component);
+                current = parent;
+            }
+            _ => return normalize_lexically(path),
         }
-        return parent_abs;
     }
-
-    path.to_path_buf()
 }
 
-fn run_native_glob(
-    workspace_root: &Path,
-    target_dir: &Path,
-    pattern: &str,
-    limit: usize,
-) -> anyhow::Result<(String, String, String, String)> {
-    if !target_dir.exists() {
-        return Ok((
-            String::new(),
-            format!("target path does not exist: {}", target_dir.display()),
-            format!(
-                "filesystem actuator filesystem_glob failed: target path {} does not exist",
-                target_dir.display()
-            ),
-            "failed".to_string(),
-        ));
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+struct GitignoreRule {
+    matcher: GlobMatcher,
+    negated: bool,
+    dir_only: bool,
+}
+
+struct GitignoreRules {
+    root: PathBuf,
+    rules: Vec<GitignoreRule>,
+}
+
+impl GitignoreRules {
+    fn load(workspace_root: &Path) -> Self {
+        let rules = fs::read_to_string(workspace_root.join(".gitignore"))
+            .map(|contents| contents.lines().filter_map(parse_gitignore_line).collect())
+            .unwrap_or_default();
+        Self {
+            root: workspace_root.to_path_buf(),
+            rules,
+        }
+    }
+
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if relative.is_empty() {
+            return false;
+        }
+        if relative == ".git" || relative.starts_with(".git/") {
+            return true;
+        }
+
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            if rule.matcher.is_match(&relative) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+fn parse_gitignore_line(line: &str) -> Option<GitignoreRule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    if line.is_empty() {
+        return None;
+    }
+
+    let pattern = match line.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if line.contains('/') => line.to_string(),
+        None => format!("**/{line}"),
+    };
+    let matcher = GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .build()
+        .ok()?
+        .compile_matcher();
+
+    Some(GitignoreRule {
+        matcher,
+        negated,
+        dir_only,
+    })
+}
+
+fn run_native_glob(
+    workspace_root: &Path,
+    target_dir: &Path,
+    pattern: &str,
+    limit: usize,
+    respect_gitignore: bool,
+) -> anyhow::Result<NativeCommandOutput> {
+    if !target_dir.exists() {
+        return Ok(NativeCommandOutput::failed(
+            format!("target path does not exist: {}", target_dir.display()),
+            format!(
+                "filesystem actuator filesystem_glob failed: target path {} does not exist",
+                target_dir.display()
+            ),
+        ));
     }
 
     let glob = Glob::new(pattern)
         .with_context(|| format!("invalid glob pattern '{pattern}'"))?
         .compile_matcher();
+    let gitignore = respect_gitignore.then(|| GitignoreRules::load(workspace_root));
 
     let mut matches = Vec::new();
     for entry in WalkDir::new(target_dir)
         .follow_links(false)
         .into_iter()
+        .filter_entry(|entry| {
+            gitignore
+                .as_ref()
+                .is_none_or(|rules| !rules.is_ignored(entry.path(), entry.file_type().is_dir()))
+        })
         .filter_map(Result::ok)
     {
         if !entry.file_type().is_file() {
@@ -2152,7 +4759,12 @@ fn run_native_glob(
         )
     };
 
-    Ok((stdout, String::new(), details, "completed".to_string()))
+    Ok(NativeCommandOutput::completed(
+        stdout,
+        String::new(),
+        details,
+        serde_json::json!({ "paths": matches }),
+    ))
 }
 
 fn run_native_grep(
@@ -2160,16 +4772,14 @@ fn run_native_grep(
     target_dir: &Path,
     pattern: &str,
     limit: usize,
-) -> anyhow::Result<(String, String, String, String)> {
+) -> anyhow::Result<NativeCommandOutput> {
     if !target_dir.exists() {
-        return Ok((
-            String::new(),
+        return Ok(NativeCommandOutput::failed(
             format!("target path does not exist: {}", target_dir.display()),
             format!(
                 "filesystem actuator filesystem_grep failed: target path {} does not exist",
                 target_dir.display()
             ),
-            "failed".to_string(),
         ));
     }
 
@@ -2177,6 +4787,7 @@ fn run_native_grep(
         Regex::new(pattern).with_context(|| format!("invalid regex pattern '{pattern}'"))?;
 
     let mut matches = Vec::new();
+    let mut hits = Vec::new();
     for entry in WalkDir::new(target_dir)
         .follow_links(false)
         .into_iter()
@@ -2201,12 +4812,13 @@ fn run_native_grep(
                 continue;
             }
 
-            matches.push(format!(
-                "{}:{}:{}",
-                display_path(workspace_root, path),
-                idx + 1,
-                line
-            ));
+            let display = display_path(workspace_root, path);
+            matches.push(format!("{}:{}:{}", display, idx + 1, line));
+            hits.push(serde_json::json!({
+                "path": display,
+                "line": idx + 1,
+                "text": line,
+            }));
             if matches.len() >= limit {
                 break;
             }
@@ -2227,49 +4839,48 @@ fn run_native_grep(
         )
     };
 
-    Ok((stdout, String::new(), details, "completed".to_string()))
+    Ok(NativeCommandOutput::completed(
+        stdout,
+        String::new(),
+        details,
+        serde_json::json!({ "hits": hits }),
+    ))
 }
 
 fn run_native_read(
     workspace_root: &Path,
     target_path: &Path,
     max_lines: usize,
-) -> anyhow::Result<(String, String, String, String)> {
+) -> anyhow::Result<NativeCommandOutput> {
     if !target_path.exists() {
-        return Ok((
-            String::new(),
+        return Ok(NativeCommandOutput::failed(
             format!("target file does not exist: {}", target_path.display()),
             format!(
                 "filesystem actuator filesystem_read failed: target file {} does not exist",
                 target_path.display()
             ),
-            "failed".to_string(),
         ));
     }
 
     if !target_path.is_file() {
-        return Ok((
-            String::new(),
+        return Ok(NativeCommandOutput::failed(
             format!("target path is not a file: {}", target_path.display()),
             format!(
                 "filesystem actuator filesystem_read failed: target path {} is not a file",
                 target_path.display()
             ),
-            "failed".to_string(),
         ));
     }
 
     let bytes = fs::read(target_path)
         .with_context(|| format!("failed to read file {}", target_path.display()))?;
     if bytes.contains(&0) {
-        return Ok((
-            String::new(),
+        return Ok(NativeCommandOutput::failed(
             format!(
                 "target file appears to be binary and cannot be read as text: {}",
                 target_path.display()
             ),
             "filesystem actuator filesystem_read failed: file is not text".to_string(),
-            "failed".to_string(),
         ));
     }
 
@@ -2298,7 +4909,127 @@ fn run_native_read(
         display_path(workspace_root, target_path)
     );
 
-    Ok((displayed, stderr, details, "completed".to_string()))
+    Ok(NativeCommandOutput::completed(
+        displayed,
+        stderr,
+        details,
+        serde_json::json!({
+            "path": display_path(workspace_root, target_path),
+            "lines": all_lines.len().min(max_lines),
+            "total_lines": all_lines.len(),
+            "truncated": truncated,
+        }),
+    ))
+}
+
+fn run_native_write(
+    workspace_root: &Path,
+    target_path: &Path,
+    contents: &str,
+    append: bool,
+) -> anyhow::Result<NativeCommandOutput> {
+    if target_path.is_dir() {
+        return Ok(NativeCommandOutput::failed(
+            format!("target path is a directory: {}", target_path.display()),
+            format!(
+                "filesystem actuator filesystem_write failed: target path {} is a directory",
+                target_path.display()
+            ),
+        ));
+    }
+
+    if let Some(parent) = target_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory {}", parent.display()))?;
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(target_path)
+        .with_context(|| format!("failed to open file {} for writing", target_path.display()))?;
+    file.write_all(contents.as_bytes())
+        .with_context(|| format!("failed to write file {}", target_path.display()))?;
+
+    let stdout = format!(
+        "{} {} bytes to {}",
+        if append { "appended" } else { "wrote" },
+        contents.len(),
+        display_path(workspace_root, target_path)
+    );
+    let details = format!(
+        "filesystem actuator filesystem_write completed for {}",
+        display_path(workspace_root, target_path)
+    );
+
+    Ok(NativeCommandOutput::completed(
+        stdout,
+        String::new(),
+        details,
+        serde_json::json!({
+            "path": display_path(workspace_root, target_path),
+            "bytes": contents.len(),
+            "append": append,
+        }),
+    ))
+}
+
+fn feedback_percept(
+    turn_id: &str,
+    feedback_depth: u32,
+    max_feedback_depth: u32,
+    feedback_notes: &[String],
+) -> Result<Percept, String> {
+    if feedback_depth >= max_feedback_depth {
+        return Err(format!(
+            "sensor action_feedback_limit: feedback depth limit {max_feedback_depth} reached; results were not fed back"
+        ));
+    }
+    Ok(Percept::ActionFeedback {
+        turn_id: format!("{turn_id}-feedback-{}", feedback_depth + 1),
+        text: format!(
+            "Results of earlier actions:\n{}\nReact to these results.",
+            feedback_notes.join("\n\n")
+        ),
+        depth: feedback_depth + 1,
+    })
+}
+
+fn format_dropped_approvals(dropped: usize) -> String {
+    if dropped == 0 {
+        String::new()
+    } else {
+        format!("; dropped {dropped} pending approval(s) for its actuators")
+    }
+}
+
+fn unmet_dependency(
+    specs: &[PlannedActionSpec],
+    actions: &[PlannedAction],
+    index: usize,
+) -> Option<String> {
+    let dependency = specs.get(index)?.depends_on?;
+    if dependency >= index {
+        return Some(format!(
+            "skipped: depends_on {dependency} must refer to an earlier action"
+        ));
+    }
+
+    let required = actions.get(dependency)?;
+    if matches!(required.status, PlannedActionStatus::Completed) {
+        return None;
+    }
+
+    Some(format!(
+        "skipped: dependency {} ({}) did not complete",
+        required.action_id, required.actuator
+    ))
+}
+
+fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
 }
 
 fn display_path(workspace_root: &Path, path: &Path) -> String {
@@ -2341,13 +5072,223 @@ fn chats_db_path() -> anyhow::Result<PathBuf> {
     Ok(dir.join("chats.sqlite"))
 }
 
+fn resolve_provider_api_key(keys: &AgentKeys, provider_name: &str) -> anyhow::Result<String> {
+    keys.api_keys
+        .iter()
+        .find(|entry| {
+            entry.provider.eq_ignore_ascii_case(provider_name) && !entry.api_key.trim().is_empty()
+        })
+        .map(|entry| entry.api_key.clone())
+        .ok_or_else(|| anyhow::anyhow!("missing API key for provider '{provider_name}'"))
+}
+
+fn export_agent_events(
+    conn: &Connection,
+    agent_id: &str,
+    session_id: Option<&str>,
+    format: ExportFormat,
+    path: &Path,
+) -> anyhow::Result<usize> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.session_id, e.turn_id, e.event_kind, e.role, e.payload_json, e.created_at
+             FROM events e
+             JOIN sessions s ON s.id = e.session_id
+             WHERE s.agent_id = ?2 AND (?1 IS NULL OR e.session_id = ?1)
+             ORDER BY e.created_at ASC, e.rowid ASC",
+        )
+        .context("failed to prepare event export query")?;
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .with_context(|| format!("failed to create export file {}", path.display()))?;
+
+    let result = write_file_atomic_with(path, |writer| -> anyhow::Result<usize> {
+        let mut rows = stmt
+            .query(params![session_id, agent_id])
+            .context("failed to query events for export")?;
+
+        if format == ExportFormat::Csv {
+            writeln!(
+                writer,
+                "id,session_id,turn_id,event_kind,role,created_at,payload_json"
+            )?;
+        }
+
+        let mut count = 0;
+        while let Some(row) = rows.next().context("failed to read exported event")? {
+            let id: String = row.get(0)?;
+            let event_session_id: String = row.get(1)?;
+            let turn_id: Option<String> = row.get(2)?;
+            let event_kind: String = row.get(3)?;
+            let role: Option<String> = row.get(4)?;
+            let payload_json: String = row.get(5)?;
+            let created_at: i64 = row.get(6)?;
+
+            match format {
+                ExportFormat::Jsonl => {
+                    let payload = serde_json::from_str::<Value>(&payload_json)
+                        .unwrap_or(Value::String(payload_json));
+                    let line = serde_json::json!({
+                        "id": id,
+                        "session_id": event_session_id,
+                        "turn_id": turn_id,
+                        "event_kind": event_kind,
+                        "role": role,
+                        "created_at": created_at,
+                        "payload": payload,
+                    });
+                    writeln!(writer, "{line}")?;
+                }
+                ExportFormat::Csv => {
+                    let fields = [
+                        id,
+                        event_session_id,
+                        turn_id.unwrap_or_default(),
+                        event_kind,
+                        role.unwrap_or_default(),
+                        created_at.to_string(),
+                        payload_json,
+                    ];
+                    let line = fields
+                        .iter()
+                        .map(|field| escape_csv_field(field))
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    writeln!(writer, "{line}")?;
+                }
+            }
+            count += 1;
+        }
+        Ok(count)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result.with_context(|| format!("failed to write export file {}", path.display()))
+}
+
+fn search_chat_events(
+    conn: &Connection,
+    agent_id: &str,
+    query: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<ChatSearchHit>> {
+    let trimmed = query.trim();
+    if trimmed.is_empty() {
+        bail!("search query cannot be empty");
+    }
+
+    let limit = limit.clamp(1, 200) as i64;
+    let fts_query = trimmed
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    match query_chat_search(
+        conn,
+        "SELECT e.session_id, e.turn_id, e.role, e.payload_json, e.created_at
+         FROM chat_search_fts f
+         JOIN chat_search_events k ON k.search_id = f.rowid
+         JOIN events e ON e.id = k.event_id
+         JOIN sessions s ON s.id = e.session_id
+         WHERE chat_search_fts MATCH ?1 AND s.agent_id = ?3
+         ORDER BY e.created_at DESC
+         LIMIT ?2",
+        &fts_query,
+        limit,
+        agent_id,
+    ) {
+        Ok(hits) => Ok(hits),
+        Err(_) => {
+            let escaped = trimmed
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            query_chat_search(
+                conn,
+                "SELECT e.session_id, e.turn_id, e.role, e.payload_json, e.created_at
+                 FROM events e
+                 JOIN sessions s ON s.id = e.session_id
+                 WHERE e.event_kind IN ('percept_user_text', 'effect_chat_response')
+                   AND e.payload_json LIKE ?1 ESCAPE '\\'
+                   AND s.agent_id = ?3
+                 ORDER BY e.created_at DESC
+                 LIMIT ?2",
+                &format!("%{escaped}%"),
+                limit,
+                agent_id,
+            )
+        }
+    }
+}
+
+fn query_chat_search(
+    conn: &Connection,
+    sql: &str,
+    pattern: &str,
+    limit: i64,
+    agent_id: &str,
+) -> anyhow::Result<Vec<ChatSearchHit>> {
+    let mut stmt = conn
+        .prepare(sql)
+        .context("failed to prepare chat search query")?;
+    let hits = stmt
+        .query_map(params![pattern, limit, agent_id], |row| {
+            Ok(ChatSearchHit {
+                session_id: row.get(0)?,
+                turn_id: row.get(1)?,
+                role: row.get(2)?,
+                text: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .context("failed to run chat search query")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to read chat search results")?;
+    Ok(hits)
+}
+
+fn positive_env<T>(var_name: &str) -> Option<T>
+where
+    T: std::str::FromStr + PartialOrd + Default,
+{
+    env::var(var_name)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<T>().ok())
+        .filter(|value| *value > T::default())
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+    field.to_string()
+}
+
 fn open_db(path: &Path) -> anyhow::Result<Connection> {
-    Connection::open(path)
-        .with_context(|| format!("failed to open sqlite db at {}", path.display()))
+    let conn = Connection::open(path)
+        .with_context(|| format!("failed to open sqlite db at {}", path.display()))?;
+    conn.busy_timeout(SQLITE_BUSY_TIMEOUT)
+        .context("failed to set sqlite busy timeout")?;
+    conn.execute_batch("PRAGMA synchronous = NORMAL;")
+        .context("failed to set sqlite synchronous mode")?;
+    Ok(conn)
 }
 
 fn initialize_db(path: &Path) -> anyhow::Result<()> {
     let conn = open_db(path)?;
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+        .context("failed to enable sqlite WAL mode")?;
     conn.execute_batch(
         "BEGIN;
          CREATE TABLE IF NOT EXISTS sessions (
@@ -2372,8 +5313,1272 @@ fn initialize_db(path: &Path) -> anyhow::Result<()> {
              ON events(session_id, created_at);
          CREATE INDEX IF NOT EXISTS idx_sessions_agent_started
              ON sessions(agent_id, started_at);
+         DROP TABLE IF EXISTS agent_starts;
+         CREATE TABLE IF NOT EXISTS agent_workspace_starts (
+             workspace_dir TEXT NOT NULL,
+             port INTEGER NOT NULL,
+             first_started_at INTEGER NOT NULL,
+             last_started_at INTEGER NOT NULL,
+             restart_count INTEGER NOT NULL DEFAULT 0,
+             PRIMARY KEY (workspace_dir, port)
+         );
+         CREATE TABLE IF NOT EXISTS action_audit (
+             id TEXT PRIMARY KEY,
+             agent_id TEXT NOT NULL,
+             session_id TEXT NOT NULL,
+             turn_id TEXT NOT NULL,
+             action_id TEXT NOT NULL,
+             plugin TEXT NOT NULL,
+             actuator TEXT NOT NULL,
+             status TEXT NOT NULL,
+             deny_reason TEXT,
+             details TEXT,
+             created_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_action_audit_actuator_created
+             ON action_audit(actuator, created_at);
+         CREATE INDEX IF NOT EXISTS idx_action_audit_agent_created
+             ON action_audit(agent_id, created_at);
+         CREATE TABLE IF NOT EXISTS model_config_history (
+             id TEXT PRIMARY KEY,
+             workspace_dir TEXT NOT NULL,
+             provider TEXT NOT NULL,
+             model TEXT NOT NULL,
+             source TEXT NOT NULL,
+             changed_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_model_config_history_workspace_changed
+             ON model_config_history(workspace_dir, changed_at);
          COMMIT;",
     )
     .context("failed to initialize chat sqlite schema")?;
+
+    if let Err(error) = initialize_chat_search(&conn) {
+        eprintln!("chat full-text search unavailable, falling back to LIKE scans: {error:#}");
+    }
     Ok(())
 }
+
+fn initialize_chat_search(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        "BEGIN;
+         DROP TRIGGER IF EXISTS events_chat_fts_insert;
+         DROP TRIGGER IF EXISTS events_chat_fts_delete;
+         DROP TABLE IF EXISTS chat_messages_fts;
+         CREATE TABLE IF NOT EXISTS chat_search_events (
+             search_id INTEGER PRIMARY KEY,
+             event_id TEXT NOT NULL UNIQUE
+         );
+         CREATE VIRTUAL TABLE IF NOT EXISTS chat_search_fts USING fts5(text);
+         CREATE TRIGGER IF NOT EXISTS events_chat_search_insert AFTER INSERT ON events
+             WHEN NEW.event_kind IN ('percept_user_text', 'effect_chat_response')
+         BEGIN
+             INSERT INTO chat_search_events (event_id) VALUES (NEW.id);
+             INSERT INTO chat_search_fts (rowid, text)
+                 SELECT search_id, NEW.payload_json FROM chat_search_events
+                 WHERE event_id = NEW.id;
+         END;
+         CREATE TRIGGER IF NOT EXISTS events_chat_search_delete AFTER DELETE ON events
+             WHEN OLD.event_kind IN ('percept_user_text', 'effect_chat_response')
+         BEGIN
+             DELETE FROM chat_search_fts WHERE rowid IN (
+                 SELECT search_id FROM chat_search_events WHERE event_id = OLD.id
+             );
+             DELETE FROM chat_search_events WHERE event_id = OLD.id;
+         END;
+         COMMIT;",
+    )
+    .context("failed to initialize chat search schema")?;
+
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin chat search backfill transaction")?;
+    let indexed_through: i64 = tx
+        .query_row(
+            "SELECT COALESCE(MAX(search_id), 0) FROM chat_search_events",
+            [],
+            |row| row.get(0),
+        )
+        .context("failed to inspect chat search index")?;
+    tx.execute(
+        "INSERT INTO chat_search_events (event_id)
+         SELECT id FROM events
+         WHERE event_kind IN ('percept_user_text', 'effect_chat_response')
+           AND id NOT IN (SELECT event_id FROM chat_search_events)
+         ORDER BY created_at, rowid",
+        [],
+    )
+    .context("failed to backfill chat search keys")?;
+    tx.execute(
+        "INSERT INTO chat_search_fts (rowid, text)
+         SELECT k.search_id, e.payload_json
+         FROM chat_search_events k
+         JOIN events e ON e.id = k.event_id
+         WHERE k.search_id > ?1",
+        params![indexed_through],
+    )
+    .context("failed to backfill chat search index")?;
+    tx.commit()
+        .context("failed to commit chat search backfill transaction")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_shell(script: &str) -> Child {
+        Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("spawn sh")
+    }
+
+    #[test]
+    fn plugin_process_returns_output() {
+        let exit = wait_for_plugin_process(
+            spawn_shell("cat"),
+            "{\"ok\":true}",
+            Duration::from_secs(5),
+            1024,
+            &TurnCancel::default(),
+        )
+        .unwrap();
+        let PluginProcessExit::Completed(output) = exit else {
+            panic!("expected completed plugin process");
+        };
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"{\"ok\":true}");
+    }
+
+    #[test]
+    fn sleeping_plugin_times_out_without_reading_stdin() {
+        let input = "x".repeat(4 * 1024 * 1024);
+        let started = Instant::now();
+        let exit = wait_for_plugin_process(
+            spawn_shell("sleep 30"),
+            &input,
+            Duration::from_millis(200),
+            1024,
+            &TurnCancel::default(),
+        )
+        .unwrap();
+        assert!(matches!(exit, PluginProcessExit::TimedOut));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn leftover_process_holding_pipes_times_out() {
+        let started = Instant::now();
+        let exit = wait_for_plugin_process(
+            spawn_shell("sleep 30 & exit 0"),
+            "",
+            Duration::from_millis(200),
+            1024,
+            &TurnCancel::default(),
+        )
+        .unwrap();
+        assert!(matches!(exit, PluginProcessExit::TimedOut));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn noisy_plugin_hits_output_limit() {
+        let exit = wait_for_plugin_process(
+            spawn_shell("yes"),
+            "",
+            Duration::from_secs(5),
+            1024,
+            &TurnCancel::default(),
+        )
+        .unwrap();
+        assert!(matches!(exit, PluginProcessExit::OutputLimitExceeded));
+    }
+
+    #[test]
+    fn cancelled_turn_stops_plugin_process() {
+        let cancel = TurnCancel::default();
+        let canceller = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+        let started = Instant::now();
+        let exit = wait_for_plugin_process(
+            spawn_shell("sleep 30"),
+            "",
+            Duration::from_secs(30),
+            1024,
+            &cancel,
+        )
+        .unwrap();
+        assert!(matches!(exit, PluginProcessExit::Cancelled));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    fn test_db() -> (PathBuf, Connection) {
+        let dir = env::temp_dir().join(format!(
+            "looper-test-{}-{}",
+            std::process::id(),
+            next_id("db")
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("chats.sqlite");
+        initialize_db(&path).unwrap();
+        let conn = open_db(&path).unwrap();
+        (dir, conn)
+    }
+
+    fn test_runtime(agent_name: Option<&str>) -> (PathBuf, PeasRuntime) {
+        let (dir, conn) = test_db();
+        drop(conn);
+        let runtime = PeasRuntime::with_db_path(
+            next_id("agent"),
+            agent_name.map(ToString::to_string),
+            dir.join("chats.sqlite"),
+        )
+        .unwrap();
+        (dir, runtime)
+    }
+
+    fn action(actuator: &str, args: Value) -> PlannedAction {
+        PlannedAction {
+            actuator: actuator.to_string(),
+            args,
+            ..planned_action("act-1", PlannedActionStatus::Planned)
+        }
+    }
+
+    fn insert_chat_event(conn: &Connection, agent_id: &str, event_id: &str, text: &str) {
+        conn.execute(
+            "INSERT OR IGNORE INTO sessions (id, agent_id, origin, started_at, metadata_json)
+             VALUES (?1, ?2, 'terminal_chat', 0, '{}')",
+            params![format!("sess-{agent_id}"), agent_id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO events (id, session_id, event_kind, role, payload_json, created_at)
+             VALUES (?1, ?2, 'percept_user_text', 'user', ?3, 0)",
+            params![event_id, format!("sess-{agent_id}"), text],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn chat_search_only_returns_this_agents_messages() {
+        let (dir, conn) = test_db();
+        insert_chat_event(&conn, "agent-a", "evt-a", "deploy the staging build");
+        insert_chat_event(&conn, "agent-b", "evt-b", "deploy the production build");
+
+        let hits = search_chat_events(&conn, "agent-a", "deploy", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "sess-agent-a");
+
+        conn.execute("DELETE FROM events WHERE id = 'evt-a'", [])
+            .unwrap();
+        assert!(
+            search_chat_events(&conn, "agent-a", "deploy", 10)
+                .unwrap()
+                .is_empty()
+        );
+        drop(conn);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    fn insert_audit_row(conn: &Connection, agent_id: &str, actuator: &str, created_at: i64) {
+        conn.execute(
+            "INSERT INTO action_audit
+                (id, agent_id, session_id, turn_id, action_id, plugin, actuator, status, created_at)
+             VALUES (?1, ?2, 'sess', 'turn', ?1, 'core', ?3, 'completed', ?4)",
+            params![next_id("audit"), agent_id, actuator, created_at],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn audit_query_filters_by_agent_actuator_and_time() {
+        let (dir, runtime) = test_runtime(None);
+        let conn = open_db(&runtime.db_path).unwrap();
+        let me = runtime.agent_id.clone();
+        insert_audit_row(&conn, &me, "shell", 100);
+        insert_audit_row(&conn, &me, "read_file", 200);
+        insert_audit_row(&conn, &me, "shell", 300);
+        insert_audit_row(&conn, "agent-other", "shell", 250);
+
+        let all = runtime.query_audit(None, None, None, 50).unwrap();
+        let times = all.iter().map(|entry| entry.created_at).collect::<Vec<_>>();
+        assert_eq!(times, vec![300, 200, 100]);
+
+        let shell = runtime.query_audit(Some("shell"), None, None, 50).unwrap();
+        assert_eq!(
+            shell
+                .iter()
+                .map(|entry| entry.created_at)
+                .collect::<Vec<_>>(),
+            vec![300, 100]
+        );
+
+        let window = runtime.query_audit(None, Some(150), Some(300), 50).unwrap();
+        assert_eq!(
+            window
+                .iter()
+                .map(|entry| entry.created_at)
+                .collect::<Vec<_>>(),
+            vec![300, 200]
+        );
+        assert_eq!(window[1].actuator, "read_file");
+        assert!(matches!(window[1].status, PlannedActionStatus::Completed));
+
+        assert_eq!(runtime.query_audit(None, None, None, 1).unwrap().len(), 1);
+        drop(conn);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn export_writes_jsonl_for_this_agents_turns() {
+        let (dir, conn) = test_db();
+        insert_chat_event(&conn, "agent-b", "evt-other", "not mine");
+        conn.execute(
+            "INSERT INTO sessions (id, agent_id, origin, started_at, metadata_json)
+             VALUES ('sess-a', 'agent-a', 'terminal_chat', 0, '{}')",
+            [],
+        )
+        .unwrap();
+        for (index, (turn_id, event_kind, role, payload)) in [
+            (
+                "turn-1",
+                "percept_user_text",
+                "user",
+                r#"{"text":"hello, \"agent\""}"#,
+            ),
+            (
+                "turn-1",
+                "effect_chat_response",
+                "assistant",
+                r#"{"text":"hi"}"#,
+            ),
+            (
+                "turn-2",
+                "percept_user_text",
+                "user",
+                r#"{"text":"line one\nline two"}"#,
+            ),
+            ("turn-2", "effect_chat_response", "assistant", "not json"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            conn.execute(
+                "INSERT INTO events (id, session_id, turn_id, event_kind, role, payload_json, created_at)
+                 VALUES (?1, 'sess-a', ?2, ?3, ?4, ?5, ?6)",
+                params![format!("evt-{index}"), turn_id, event_kind, role, payload, index as i64],
+            )
+            .unwrap();
+        }
+
+        let path = dir.join("export/events.jsonl");
+        let count =
+            export_agent_events(&conn, "agent-a", None, ExportFormat::Jsonl, &path).unwrap();
+        assert_eq!(count, 4);
+        let lines = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["turn_id"], "turn-1");
+        assert_eq!(lines[0]["payload"]["text"], "hello, \"agent\"");
+        assert_eq!(lines[2]["payload"]["text"], "line one\nline two");
+        assert_eq!(lines[3]["turn_id"], "turn-2");
+        assert_eq!(lines[3]["payload"], "not json");
+        assert!(lines.iter().all(|line| line["session_id"] == "sess-a"));
+
+        assert!(export_agent_events(&conn, "agent-a", None, ExportFormat::Jsonl, &path).is_err());
+        let other = dir.join("other.jsonl");
+        let count = export_agent_events(
+            &conn,
+            "agent-a",
+            Some("sess-agent-b"),
+            ExportFormat::Jsonl,
+            &other,
+        )
+        .unwrap();
+        assert_eq!(count, 0);
+        drop(conn);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn chat_search_backfills_unindexed_messages_on_restart() {
+        let (dir, conn) = test_db();
+        insert_chat_event(&conn, "agent-a", "evt-indexed", "first note");
+        conn.execute_batch(
+            "DROP TRIGGER events_chat_search_insert;
+             INSERT INTO events (id, session_id, event_kind, role, payload_json, created_at)
+             VALUES ('evt-missed', 'sess-agent-a', 'percept_user_text', 'user', 'second note', 0);",
+        )
+        .unwrap();
+        assert_eq!(
+            search_chat_events(&conn, "agent-a", "note", 10)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        initialize_chat_search(&conn).unwrap();
+        initialize_chat_search(&conn).unwrap();
+        assert_eq!(
+            search_chat_events(&conn, "agent-a", "note", 10)
+                .unwrap()
+                .len(),
+            2
+        );
+        drop(conn);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    fn action_spec(depends_on: Option<usize>) -> PlannedActionSpec {
+        PlannedActionSpec {
+            plugin: None,
+            actuator: "shell_command".to_string(),
+            args: Value::Null,
+            depends_on,
+            feedback: false,
+        }
+    }
+
+    fn planned_action(action_id: &str, status: PlannedActionStatus) -> PlannedAction {
+        PlannedAction {
+            action_id: action_id.to_string(),
+            plugin: "auto".to_string(),
+            actuator: "shell_command".to_string(),
+            args: Value::Null,
+            status,
+            details: None,
+            result: None,
+            deny_reason: None,
+        }
+    }
+
+    #[test]
+    fn unmet_dependency_checks_earlier_action_status() {
+        let specs = [
+            action_spec(None),
+            action_spec(Some(0)),
+            action_spec(Some(1)),
+        ];
+
+        let completed = [planned_action("act-1", PlannedActionStatus::Completed)];
+        assert_eq!(unmet_dependency(&specs, &completed, 0), None);
+        assert_eq!(unmet_dependency(&specs, &completed, 1), None);
+
+        let failed = [planned_action("act-1", PlannedActionStatus::Failed)];
+        assert_eq!(
+            unmet_dependency(&specs, &failed, 1).as_deref(),
+            Some("skipped: dependency act-1 (shell_command) did not complete")
+        );
+    }
+
+    #[test]
+    fn unmet_dependency_rejects_forward_references() {
+        let specs = [action_spec(Some(1)), action_spec(Some(1))];
+        let expected = Some("skipped: depends_on 1 must refer to an earlier action");
+        assert_eq!(unmet_dependency(&specs, &[], 0).as_deref(), expected);
+        assert_eq!(unmet_dependency(&specs, &[], 1).as_deref(), expected);
+    }
+
+    fn monday_plus(days: u64, hours: u64, minutes: u64) -> SystemTime {
+        let first_monday = 4 * 24 * 60 * 60;
+        UNIX_EPOCH + Duration::from_secs(first_monday + ((days * 24 + hours) * 60 + minutes) * 60)
+    }
+
+    fn schedule(windows: &[&str], utc_offset_minutes: i64) -> ActionSchedule {
+        ActionSchedule {
+            windows: windows
+                .iter()
+                .map(|window| parse_time_window(window).unwrap())
+                .collect(),
+            utc_offset_minutes,
+        }
+    }
+
+    #[test]
+    fn schedule_allows_weekday_hours() {
+        let schedule = schedule(&["mon-fri 09:00-17:00"], 0);
+        assert!(schedule.allows(monday_plus(0, 9, 0)));
+        assert!(schedule.allows(monday_plus(4, 16, 59)));
+        assert!(!schedule.allows(monday_plus(0, 17, 0)));
+        assert!(!schedule.allows(monday_plus(0, 8, 59)));
+        assert!(!schedule.allows(monday_plus(5, 12, 0)));
+    }
+
+    #[test]
+    fn schedule_window_can_cross_midnight() {
+        let schedule = schedule(&["fri 22:00-02:00"], 0);
+        assert!(schedule.allows(monday_plus(4, 23, 0)));
+        assert!(schedule.allows(monday_plus(5, 1, 59)));
+        assert!(!schedule.allows(monday_plus(5, 2, 0)));
+        assert!(!schedule.allows(monday_plus(3, 23, 0)));
+        assert!(!schedule.allows(monday_plus(4, 1, 0)));
+    }
+
+    #[test]
+    fn schedule_applies_utc_offset() {
+        let schedule = schedule(&["mon 09:00-10:00"], parse_utc_offset("+02:00").unwrap());
+        assert!(schedule.allows(monday_plus(0, 7, 30)));
+        assert!(!schedule.allows(monday_plus(0, 9, 30)));
+    }
+
+    #[test]
+    fn risk_pattern_matches_nested_args_after_normalizing() {
+        let patterns = DEFAULT_RISK_PATTERNS.map(ToString::to_string);
+        let args = serde_json::json!({
+            "cwd": "/tmp",
+            "steps": [{ "command": "cd build &&  RM   -RF  ./out" }],
+        });
+        assert_eq!(matching_risk_pattern(&patterns, &args), Some("rm -rf"));
+    }
+
+    #[test]
+    fn risk_pattern_ignores_safe_and_non_string_args() {
+        let patterns = DEFAULT_RISK_PATTERNS.map(ToString::to_string);
+        let args = serde_json::json!({ "command": "git status", "force": true, "depth": 3 });
+        assert_eq!(matching_risk_pattern(&patterns, &args), None);
+        assert_eq!(
+            matching_risk_pattern(&[], &serde_json::json!("rm -rf /")),
+            None
+        );
+    }
+
+    fn temp_workspace() -> PathBuf {
+        let root = env::temp_dir().join(format!(
+            "looper-test-{}-{}",
+            std::process::id(),
+            next_id("fs")
+        ));
+        fs::create_dir_all(root.join("workspace")).unwrap();
+        fs::canonicalize(root).unwrap()
+    }
+
+    #[test]
+    fn canonicalize_for_check_collapses_missing_parent_segments() {
+        let root = temp_workspace();
+        let workspace = root.join("workspace");
+
+        assert_eq!(
+            canonicalize_for_check(&workspace.join("new/../notes.txt")),
+            workspace.join("notes.txt")
+        );
+        let escaped = canonicalize_for_check(&workspace.join("new/../../outside.txt"));
+        assert_eq!(escaped, root.join("outside.txt"));
+        assert!(!escaped.starts_with(&workspace));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn canonicalize_for_check_resolves_existing_symlinks() {
+        let root = temp_workspace();
+        std::os::unix::fs::symlink(root.join("workspace"), root.join("link")).unwrap();
+
+        assert_eq!(
+            canonicalize_for_check(&root.join("link/new/file.txt")),
+            root.join("workspace/new/file.txt")
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn breaker_opens_after_failures_and_closes_after_trial() {
+        let (dir, mut runtime) = test_runtime(None);
+        runtime.breaker_config = BreakerConfig {
+            failures: 3,
+            window: Duration::from_secs(300),
+            cooldown: Duration::from_millis(50),
+        };
+        let key = ("webhook-notify".to_string(), "webhook_post".to_string());
+
+        runtime.record_actuator_failure(&key);
+        runtime.record_actuator_failure(&key);
+        assert!(runtime.circuit_retry_in(&key).is_none());
+        runtime.record_actuator_failure(&key);
+        assert!(runtime.circuit_retry_in(&key).is_some());
+        assert_eq!(
+            runtime.circuit_states("webhook-notify"),
+            ["webhook_post:open(1s)"]
+        );
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(runtime.circuit_retry_in(&key).is_none());
+        assert_eq!(
+            runtime.circuit_states("webhook-notify"),
+            ["webhook_post:half_open"]
+        );
+        runtime.record_actuator_failure(&key);
+        assert!(runtime.circuit_retry_in(&key).is_some());
+
+        thread::sleep(Duration::from_millis(60));
+        runtime.record_actuator_success(&key);
+        assert!(runtime.circuit_retry_in(&key).is_none());
+        assert!(runtime.circuit_states("webhook-notify").is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn filesystem_input_errors_do_not_trip_breaker() {
+        let (dir, mut runtime) = test_runtime(None);
+        runtime.breaker_config.failures = 1;
+        let plugins = runtime.builtin_plugins();
+        let workspace_dir = dir.to_string_lossy().to_string();
+
+        for action in [
+            action(
+                "filesystem_read",
+                serde_json::json!({ "file_path": "missing.txt" }),
+            ),
+            action("filesystem_grep", serde_json::json!({ "pattern": "(" })),
+        ] {
+            let outcome = runtime
+                .execute_planned_action(
+                    &plugins,
+                    &workspace_dir,
+                    &action,
+                    PermissionMode::AllowOneShot,
+                    &TurnCancel::default(),
+                )
+                .unwrap()
+                .unwrap();
+            assert_eq!(outcome.status, "failed");
+        }
+        assert!(runtime.circuit_states("filesystem-read").is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn sensor_soul_overrides_workspace_soul_and_falls_back() {
+        let (dir, runtime) = test_runtime(None);
+        let workspace = dir.join("workspace");
+        let plugin_root = dir.join("plugin");
+        fs::create_dir_all(workspace.join("personas")).unwrap();
+        fs::create_dir_all(&plugin_root).unwrap();
+        fs::write(workspace.join("SOUL.md"), "workspace soul").unwrap();
+        fs::write(workspace.join("personas/terse.md"), "terse soul").unwrap();
+        fs::write(plugin_root.join("soul.md"), "plugin soul").unwrap();
+        fs::write(dir.join("outside.md"), "outside soul").unwrap();
+        let workspace_dir = workspace.to_string_lossy().to_string();
+        let prompt = |soul: &str| {
+            runtime.build_chat_system_prompt(
+                &[],
+                None,
+                &workspace_dir,
+                contained_soul_path(&workspace, &plugin_root, soul),
+            )
+        };
+
+        assert!(prompt("personas/terse.md").contains("terse soul"));
+        assert!(!prompt("personas/terse.md").contains("workspace soul"));
+        assert!(prompt(&plugin_root.join("soul.md").to_string_lossy()).contains("plugin soul"));
+        assert!(prompt("personas/missing.md").contains("workspace soul"));
+
+        assert!(contained_soul_path(&workspace, &plugin_root, "../outside.md").is_none());
+        let outside = dir.join("outside.md").to_string_lossy().to_string();
+        assert!(contained_soul_path(&workspace, &plugin_root, &outside).is_none());
+        assert!(prompt("../outside.md").contains("workspace soul"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn external_plugin_net_guard_copies_match_shared_source() {
+        let shared = include_str!("../../external-plugins/shared/net-guard.ts");
+        assert_eq!(
+            include_str!("../../external-plugins/http-fetch/net-guard.ts"),
+            shared
+        );
+        assert_eq!(
+            include_str!("../../external-plugins/webhook-notify/net-guard.ts"),
+            shared
+        );
+    }
+
+    #[test]
+    fn agent_starts_are_counted_per_workspace_and_port() {
+        let (dir, runtime) = test_runtime(None);
+        let first = runtime.record_agent_start("/work/a", 11000).unwrap();
+        assert_eq!(first.restart_count, 0);
+
+        thread::sleep(Duration::from_millis(5));
+        let restarted =
+            PeasRuntime::with_db_path(next_id("agent"), None, dir.join("chats.sqlite")).unwrap();
+        let second = restarted.record_agent_start("/work/a", 11000).unwrap();
+        assert_eq!(second.restart_count, 1);
+        assert_eq!(second.first_started_at, first.first_started_at);
+
+        let other = restarted.record_agent_start("/work/b", 11000).unwrap();
+        assert_eq!(other.restart_count, 0);
+        assert!(other.first_started_at > first.first_started_at);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    fn insert_session(conn: &Connection, id: &str, agent_id: &str, started_at: i64, ended: bool) {
+        conn.execute(
+            "INSERT INTO sessions (id, agent_id, origin, started_at, ended_at, metadata_json)
+             VALUES (?1, ?2, 'terminal_chat', ?3, ?4, '{}')",
+            params![id, agent_id, started_at, ended.then_some(started_at + 10)],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO events (id, session_id, event_kind, role, payload_json, created_at)
+             VALUES (?1, ?2, 'percept_user_text', 'user', '\"hi\"', ?3)",
+            params![format!("evt-{id}"), id, started_at],
+        )
+        .unwrap();
+    }
+
+    fn session_ids(conn: &Connection) -> Vec<String> {
+        let mut statement = conn.prepare("SELECT id FROM sessions ORDER BY id").unwrap();
+        statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn pruning_only_removes_this_agents_ended_sessions() {
+        let (dir, runtime) = test_runtime(None);
+        let conn = open_db(&runtime.db_path).unwrap();
+        let me = runtime.agent_id.clone();
+        insert_session(&conn, "a-old", &me, 100, true);
+        insert_session(&conn, "a-old-open", &me, 100, false);
+        insert_session(&conn, "a-new", &me, 5_000, true);
+        insert_session(&conn, "b-old", "agent-other", 100, true);
+
+        assert_eq!(runtime.prune_sessions_older_than(1_000).unwrap(), 1);
+        assert_eq!(session_ids(&conn), vec!["a-new", "a-old-open", "b-old"]);
+        let orphaned: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM events WHERE session_id = 'a-old'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(orphaned, 0);
+
+        insert_session(&conn, "a-newer", &me, 6_000, true);
+        insert_session(&conn, "b-older", "agent-other", 50, true);
+        assert_eq!(runtime.prune_sessions_keep_last(1).unwrap(), 1);
+        assert_eq!(
+            session_ids(&conn),
+            vec!["a-newer", "a-old-open", "b-old", "b-older"]
+        );
+        drop(conn);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_paths_stay_inside_write_roots() {
+        let (dir, runtime) = test_runtime(None);
+        let plugins = runtime.builtin_plugins();
+        let plugin = runtime
+            .plugin_by_name(&plugins, "filesystem-write")
+            .unwrap();
+        let workspace = dir.join("workspace");
+        fs::create_dir_all(&workspace).unwrap();
+        std::os::unix::fs::symlink(&dir, workspace.join("escape")).unwrap();
+
+        assert!(is_allowed_write_path(
+            plugin,
+            &workspace,
+            &workspace.join("notes/a.txt")
+        ));
+        assert!(!is_allowed_write_path(
+            plugin,
+            &workspace,
+            &workspace.join("../a.txt")
+        ));
+        assert!(!is_allowed_write_path(
+            plugin,
+            &workspace,
+            &workspace.join("notes/../../a.txt")
+        ));
+        assert!(!is_allowed_write_path(
+            plugin,
+            &workspace,
+            &workspace.join("escape/a.txt")
+        ));
+        assert!(!is_allowed_write_path(
+            plugin,
+            &workspace,
+            &dir.join("a.txt")
+        ));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn native_write_appends_or_truncates() {
+        let workspace = temp_workspace();
+        let target = workspace.join("notes/log.txt");
+
+        run_native_write(&workspace, &target, "first\n", false).unwrap();
+        run_native_write(&workspace, &target, "second\n", true).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "first\nsecond\n");
+
+        let output = run_native_write(&workspace, &target, "fresh\n", false).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "fresh\n");
+        assert_eq!(output.structured.unwrap()["append"], false);
+
+        let output = run_native_write(&workspace, &workspace.join("notes"), "x", false).unwrap();
+        assert!(output.details.contains("is a directory"));
+        let _ = fs::remove_dir_all(workspace);
+    }
+
+    #[test]
+    fn filesystem_write_is_blocked_under_enforce() {
+        let (dir, runtime) = test_runtime(None);
+        let plugins = runtime.builtin_plugins();
+        let workspace_dir = dir.to_string_lossy().to_string();
+        let write = action(
+            "filesystem_write",
+            serde_json::json!({ "file_path": "out.txt", "contents": "hello" }),
+        );
+
+        let blocked = runtime
+            .execute_planned_action(
+                &plugins,
+                &workspace_dir,
+                &write,
+                PermissionMode::Enforce,
+                &TurnCancel::default(),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(blocked.status, "blocked");
+        assert!(matches!(
+            blocked.deny_reason,
+            Some(DenyReason::WriteNeedsApproval)
+        ));
+        assert!(!dir.join("out.txt").exists());
+
+        let escaping = action(
+            "filesystem_write",
+            serde_json::json!({ "file_path": "../out.txt", "contents": "hello" }),
+        );
+        let refused = runtime
+            .execute_planned_action(
+                &plugins,
+                &workspace_dir,
+                &escaping,
+                PermissionMode::AllowOneShot,
+                &TurnCancel::default(),
+            )
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            refused.deny_reason,
+            Some(DenyReason::OutsideWriteRoots)
+        ));
+
+        let written = runtime
+            .execute_planned_action(
+                &plugins,
+                &workspace_dir,
+                &write,
+                PermissionMode::AllowOneShot,
+                &TurnCancel::default(),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(written.status, "completed");
+        assert_eq!(fs::read_to_string(dir.join("out.txt")).unwrap(), "hello");
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn gitignore_rules_follow_git_matching() {
+        let root = temp_workspace();
+        fs::write(
+            root.join(".gitignore"),
+            "# build output\ntarget/\n*.log\n!keep.log\n/dist\ndocs/*.tmp\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("nested")).unwrap();
+        fs::write(root.join("nested/.gitignore"), "*.rs\n").unwrap();
+        let rules = GitignoreRules::load(&root);
+        let ignored = |path: &str, is_dir: bool| rules.is_ignored(&root.join(path), is_dir);
+
+        assert!(ignored("target", true));
+        assert!(ignored("crates/app/target", true));
+        assert!(!ignored("target", false));
+
+        assert!(ignored("debug.log", false));
+        assert!(ignored("logs/debug.log", false));
+        assert!(!ignored("keep.log", false));
+        assert!(!ignored("logs/keep.log", false));
+
+        assert!(ignored("dist", true));
+        assert!(!ignored("web/dist", true));
+        assert!(ignored("docs/draft.tmp", false));
+        assert!(!ignored("docs/nested/draft.tmp", false));
+        assert!(!ignored("other/docs/draft.tmp", false));
+
+        assert!(ignored(".git", true));
+        assert!(ignored(".git/HEAD", false));
+        assert!(!ignored(".github", true));
+        assert!(!ignored("nested/main.rs", false));
+        assert!(!rules.is_ignored(&env::temp_dir().join("debug.log"), false));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn percept_transforms_apply_in_order() {
+        let transforms = vec![
+            PerceptTransform::StripAnsi,
+            PerceptTransform::Trim,
+            PerceptTransform::Lowercase,
+            PerceptTransform::Regex {
+                find: r"\s+".to_string(),
+                replace: " ".to_string(),
+            },
+            PerceptTransform::Truncate { max_chars: 9 },
+        ];
+        let text = apply_percept_transforms(
+            &transforms,
+            "  \x1b[31mRED\x1b[0m   Alert\x1b]0;title\x07 ÜBER  ".to_string(),
+        )
+        .unwrap();
+        assert_eq!(text, "red alert");
+
+        let text =
+            apply_percept_transforms(&[PerceptTransform::Truncate { max_chars: 2 }], "ÜÜÜ".into())
+                .unwrap();
+        assert_eq!(text, "ÜÜ");
+        assert_eq!(
+            apply_percept_transforms(&[], " as is ".into()).unwrap(),
+            " as is "
+        );
+
+        let bad = [PerceptTransform::Regex {
+            find: "(".to_string(),
+            replace: String::new(),
+        }];
+        assert!(apply_percept_transforms(&bad, "text".into()).is_err());
+    }
+
+    fn write_test_plugin(plugins_root: &Path, name: &str, peas: Value) -> PathBuf {
+        let plugin_dir = plugins_root.join(name);
+        fs::create_dir_all(&plugin_dir).unwrap();
+        fs::write(plugin_dir.join("main.ts"), "").unwrap();
+        fs::write(
+            plugin_dir.join("looper-plugin.json"),
+            serde_json::json!({
+                "name": name,
+                "description": "test",
+                "version": "0.1.0",
+                "entry": "main.ts",
+                "permissions": {},
+                "peas": peas,
+            })
+            .to_string(),
+        )
+        .unwrap();
+        plugin_dir
+    }
+
+    #[test]
+    fn plugin_loaders_reject_invalid_transform_patterns() {
+        let root = temp_workspace();
+        let plugin_dir = write_test_plugin(
+            &root.join("plugins"),
+            "bad-transform",
+            serde_json::json!({
+                "sensors": [{
+                    "name": "input",
+                    "description": "test",
+                    "transforms": [{ "kind": "regex", "find": "(", "replace": "" }]
+                }]
+            }),
+        );
+
+        let error = load_plugin_from_dir(&plugin_dir).unwrap_err();
+        assert!(format!("{error:#}").contains("invalid transform pattern"));
+        let error = load_plugins(&root.join("plugins")).unwrap_err();
+        assert!(format!("{error:#}").contains("invalid transform pattern"));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn plugin_process_actuators_require_approval_by_default() {
+        let (dir, mut runtime) = test_runtime(None);
+        let plugin_dir = write_test_plugin(
+            &dir.join("plugins"),
+            "deploy",
+            serde_json::json!({
+                "actuators": [
+                    { "name": "deploy_run", "description": "test" },
+                    { "name": "deploy_status", "description": "test", "require_approval": false }
+                ]
+            }),
+        );
+        let process_plugin = load_plugin_from_dir(&plugin_dir).unwrap();
+        let plugins = runtime.builtin_plugins();
+        let read_plugin = runtime.plugin_by_name(&plugins, "filesystem-read").unwrap();
+
+        assert!(runtime.actuator_requires_approval(&process_plugin, "deploy_run"));
+        assert!(!runtime.actuator_requires_approval(&process_plugin, "deploy_status"));
+        assert!(!runtime.actuator_requires_approval(read_plugin, "filesystem_read"));
+
+        runtime.approval_defaults =
+            parse_approval_defaults(Some("plugin_process=open, native_filesystem=REQUIRED"))
+                .unwrap();
+        assert!(!runtime.actuator_requires_approval(&process_plugin, "deploy_run"));
+        assert!(runtime.actuator_requires_approval(read_plugin, "filesystem_read"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn approval_defaults_reject_malformed_entries() {
+        let defaults = parse_approval_defaults(None).unwrap();
+        assert_eq!(defaults.get("plugin_process"), Some(&true));
+        assert_eq!(defaults.get("native_filesystem"), Some(&false));
+        assert_eq!(parse_approval_defaults(Some(" , ")).unwrap(), defaults);
+
+        for (raw, message) in [
+            ("plugin_process", "expected <executor>=<required|open>"),
+            ("shell=open", "unknown executor 'shell'"),
+            ("plugin_process=maybe", "got 'maybe'"),
+        ] {
+            let error = parse_approval_defaults(Some(raw)).unwrap_err().to_string();
+            assert!(error.contains(message), "{error}");
+            assert!(error.contains(ACTUATOR_APPROVAL_ENV));
+        }
+    }
+
+    #[test]
+    fn soul_path_override_resolves_against_workspace() {
+        let workspace = Path::new("/work/space");
+        assert_eq!(
+            soul_path_with_override(workspace, None),
+            workspace.join("SOUL.md")
+        );
+        assert_eq!(
+            soul_path_with_override(workspace, Some("  ")),
+            workspace.join("SOUL.md")
+        );
+        assert_eq!(
+            soul_path_with_override(workspace, Some("personas/calm.md")),
+            workspace.join("personas/calm.md")
+        );
+        assert_eq!(
+            soul_path_with_override(workspace, Some("/etc/looper/soul.md")),
+            PathBuf::from("/etc/looper/soul.md")
+        );
+    }
+
+    #[test]
+    fn soul_cache_reloads_when_mtime_or_len_changes() {
+        let (dir, runtime) = test_runtime(None);
+        let soul_path = dir.join("SOUL.md");
+        let set_mtime = |offset_secs: u64| {
+            fs::File::options()
+                .write(true)
+                .open(&soul_path)
+                .unwrap()
+                .set_modified(
+                    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + offset_secs),
+                )
+                .unwrap();
+        };
+
+        fs::write(&soul_path, "calm").unwrap();
+        set_mtime(0);
+        assert!(
+            runtime
+                .load_soul_prompt(soul_path.clone())
+                .unwrap()
+                .contains("calm")
+        );
+
+        fs::write(&soul_path, "loud").unwrap();
+        set_mtime(0);
+        assert!(
+            runtime
+                .load_soul_prompt(soul_path.clone())
+                .unwrap()
+                .contains("calm")
+        );
+
+        set_mtime(60);
+        assert!(
+            runtime
+                .load_soul_prompt(soul_path.clone())
+                .unwrap()
+                .contains("loud")
+        );
+
+        fs::write(&soul_path, "louder").unwrap();
+        set_mtime(60);
+        assert!(
+            runtime
+                .load_soul_prompt(soul_path.clone())
+                .unwrap()
+                .contains("louder")
+        );
+
+        fs::remove_file(&soul_path).unwrap();
+        assert!(runtime.load_soul_prompt(soul_path).is_none());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn action_feedback_stops_at_the_depth_limit() {
+        let notes = vec!["read ok".to_string(), "grep ok".to_string()];
+        let Ok(Percept::ActionFeedback {
+            turn_id,
+            text,
+            depth,
+        }) = feedback_percept("turn-1", 0, 2, &notes)
+        else {
+            panic!("expected a feedback percept below the limit");
+        };
+        assert_eq!(turn_id, "turn-1-feedback-1");
+        assert_eq!(depth, 1);
+        assert!(text.contains("read ok\n\ngrep ok"));
+
+        let Ok(Percept::ActionFeedback { turn_id, depth, .. }) =
+            feedback_percept("turn-1-feedback-1", 1, 2, &notes)
+        else {
+            panic!("expected a second feedback percept");
+        };
+        assert_eq!(turn_id, "turn-1-feedback-1-feedback-2");
+        assert_eq!(depth, 2);
+
+        let note = feedback_percept("turn-1", 2, 2, &notes).unwrap_err();
+        assert!(note.contains("feedback depth limit 2 reached"));
+        assert!(feedback_percept("turn-1", 0, 0, &notes).is_err());
+    }
+
+    #[test]
+    fn action_limit_defers_or_drops_overflow() {
+        let (dir, mut runtime) = test_runtime(None);
+        let limited =
+            |index: usize| planned_action(&format!("act-{index}"), PlannedActionStatus::Planned);
+
+        let mut first = limited(0);
+        runtime.action_limit.max_per_turn = None;
+        assert!(runtime.apply_action_limit("sess", 5, &mut first).is_none());
+        runtime.action_limit = ActionLimit {
+            max_per_turn: Some(1),
+            overflow: ActionOverflow::Defer,
+        };
+        assert!(runtime.apply_action_limit("sess", 0, &mut first).is_none());
+        assert!(matches!(first.status, PlannedActionStatus::Planned));
+
+        let mut deferred = limited(1);
+        let note = runtime
+            .apply_action_limit("sess", 1, &mut deferred)
+            .unwrap();
+        assert!(note.contains("limited to 1 actions"));
+        assert!(matches!(
+            deferred.status,
+            PlannedActionStatus::AwaitingApproval
+        ));
+        assert!(matches!(
+            deferred.deny_reason,
+            Some(DenyReason::ActionLimit)
+        ));
+        let pending = runtime.take_pending_approvals("sess");
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].action.action_id, "act-1");
+
+        runtime.action_limit.overflow = ActionOverflow::Drop;
+        let mut dropped = limited(2);
+        assert!(
+            runtime
+                .apply_action_limit("sess", 2, &mut dropped)
+                .is_some()
+        );
+        assert!(matches!(dropped.status, PlannedActionStatus::Skipped));
+        assert!(dropped.details.unwrap().starts_with("Dropped"));
+        assert!(runtime.take_pending_approvals("sess").is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn snapshot_restores_pending_state_into_a_new_runtime() {
+        let (dir, runtime) = test_runtime(None);
+        runtime.push_pending_approval(
+            "sess-a",
+            PendingApproval {
+                action: planned_action("act-7", PlannedActionStatus::AwaitingApproval),
+                reason: "needs approval".to_string(),
+            },
+        );
+        runtime.set_pending_feedback(
+            "sess-a",
+            Percept::ActionFeedback {
+                turn_id: "turn-1-feedback-1".to_string(),
+                text: "Results of earlier actions".to_string(),
+                depth: 1,
+            },
+        );
+        let text = serde_json::to_string(&runtime.state_snapshot()).unwrap();
+
+        let restored =
+            PeasRuntime::with_db_path(next_id("agent"), None, dir.join("chats.sqlite")).unwrap();
+        restored.push_pending_approval(
+            "sess-stale",
+            PendingApproval {
+                action: planned_action("act-stale", PlannedActionStatus::AwaitingApproval),
+                reason: "stale".to_string(),
+            },
+        );
+        restored.restore_from_snapshot(serde_json::from_str(&text).unwrap());
+
+        let pending = restored.take_pending_approvals("sess-a");
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].action.action_id, "act-7");
+        assert_eq!(pending[0].reason, "needs approval");
+        assert!(restored.take_pending_approvals("sess-stale").is_empty());
+        let Some(Percept::ActionFeedback { turn_id, depth, .. }) =
+            restored.take_pending_feedback("sess-a")
+        else {
+            panic!("pending feedback was not restored");
+        };
+        assert_eq!(turn_id, "turn-1-feedback-1");
+        assert_eq!(depth, 1);
+
+        restored.restore_from_snapshot(serde_json::from_str("{}").unwrap());
+        assert!(restored.state_snapshot().pending_approvals.is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn assistant_events_use_the_display_name_as_role() {
+        for (agent_name, expected) in [(Some("Nova"), "Nova"), (None, "assistant")] {
+            let (dir, runtime) = test_runtime(agent_name);
+            let response = Effect::ChatResponse {
+                turn_id: "turn-1".to_string(),
+                text: "hello".to_string(),
+            };
+            let session_id = runtime.start_session(SessionOrigin::TerminalChat).unwrap();
+            runtime.record_effect(&session_id, &response).unwrap();
+
+            let conn = open_db(&runtime.db_path).unwrap();
+            let role: String = conn
+                .query_row(
+                    "SELECT role FROM events WHERE event_kind = 'effect_chat_response'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(role, expected);
+            drop(conn);
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
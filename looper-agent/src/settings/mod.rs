@@ -1,10 +1,28 @@
+use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
 
 use anyhow::{Context, bail};
-use looper_common::ProviderApiKey;
+use looper_common::{ProviderApiKey, write_file_atomic};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+const REDACTED: &str = "[redacted]";
+const MIN_REGISTERED_SECRET_LEN: usize = 8;
+pub const CONFIG_BUNDLE_VERSION: u32 = 1;
+
+static KNOWN_SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static SECRET_PATTERNS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        r"\bsk-[A-Za-z0-9_\-]{8,}",
+        r"(?i)\bbearer\s+[A-Za-z0-9._\-]{8,}",
+    ]
+    .iter()
+    .filter_map(|pattern| Regex::new(pattern).ok())
+    .collect()
+});
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentSettings {
     pub workspace_dir: String,
@@ -20,6 +38,25 @@ pub struct AgentKeys {
     pub api_keys: Vec<ProviderApiKey>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub version: u32,
+    pub provider: String,
+    pub model: String,
+    #[serde(default)]
+    pub plugins: Vec<PluginOverride>,
+    #[serde(default)]
+    pub keys_included: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<ProviderApiKey>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginOverride {
+    pub name: String,
+    pub enabled: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct PersistedAgentConfig {
     pub settings: AgentSettings,
@@ -43,6 +80,7 @@ pub fn load_persisted_config(workspace_dir: &Path) -> anyhow::Result<Option<Pers
         .with_context(|| format!("invalid settings file {}", settings_path.display()))?;
     let keys: AgentKeys = serde_json::from_str(&keys_text)
         .with_context(|| format!("invalid keys file {}", keys_path.display()))?;
+    register_secrets(&keys);
 
     if settings.workspace_dir.trim().is_empty() {
         bail!("settings.json has empty workspace_dir");
@@ -71,6 +109,7 @@ pub fn persist_config(
     settings: AgentSettings,
     keys: AgentKeys,
 ) -> anyhow::Result<PersistedAgentConfig> {
+    register_secrets(&keys);
     fs::create_dir_all(workspace_dir)
         .with_context(|| format!("failed to create workspace {}", workspace_dir.display()))?;
 
@@ -80,14 +119,104 @@ pub fn persist_config(
     let settings_text = serde_json::to_string_pretty(&settings).context("serialize settings")?;
     let keys_text = serde_json::to_string_pretty(&keys).context("serialize keys")?;
 
-    fs::write(&settings_path, settings_text)
+    write_file_atomic(&settings_path, settings_text.as_bytes())
         .with_context(|| format!("failed to write {}", settings_path.display()))?;
-    fs::write(&keys_path, keys_text)
+    write_file_atomic(&keys_path, keys_text.as_bytes())
         .with_context(|| format!("failed to write {}", keys_path.display()))?;
 
     Ok(PersistedAgentConfig { settings, keys })
 }
 
+pub fn read_config_bundle(path: &Path) -> anyhow::Result<ConfigBundle> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let bundle: ConfigBundle = serde_json::from_str(&text)
+        .with_context(|| format!("invalid config bundle {}", path.display()))?;
+
+    if bundle.version != CONFIG_BUNDLE_VERSION {
+        bail!(
+            "config bundle version {} is not supported (expected {})",
+            bundle.version,
+            CONFIG_BUNDLE_VERSION
+        );
+    }
+    if bundle.provider.trim().is_empty() {
+        bail!("config bundle has empty provider");
+    }
+    if bundle.model.trim().is_empty() {
+        bail!("config bundle has empty model");
+    }
+
+    let mut seen = Vec::new();
+    for plugin in &bundle.plugins {
+        let name = plugin.name.trim();
+        if name.is_empty() {
+            bail!("config bundle has a plugin entry with an empty name");
+        }
+        if seen.contains(&name) {
+            bail!("config bundle lists plugin '{name}' more than once");
+        }
+        seen.push(name);
+    }
+    for key in &bundle.api_keys {
+        if key.provider.trim().is_empty() || key.api_key.trim().is_empty() {
+            bail!("config bundle has an API key entry with an empty provider or key");
+        }
+    }
+
+    Ok(bundle)
+}
+
+pub fn write_config_bundle(path: &Path, bundle: &ConfigBundle) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let text = serde_json::to_string_pretty(bundle).context("serialize config bundle")?;
+    write_file_atomic(path, text.as_bytes())
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+pub fn register_secrets(keys: &AgentKeys) {
+    for key in &keys.api_keys {
+        register_secret(&key.api_key);
+    }
+}
+
+pub fn register_secret(secret: &str) {
+    let Ok(mut known) = KNOWN_SECRETS.lock() else {
+        return;
+    };
+    let secret = secret.trim();
+    if secret.len() >= MIN_REGISTERED_SECRET_LEN && !known.iter().any(|entry| entry == secret) {
+        known.push(secret.to_string());
+    }
+}
+
+pub fn redact_secrets(text: &str) -> String {
+    let mut redacted = text.to_string();
+    if let Ok(known) = KNOWN_SECRETS.lock() {
+        for secret in known.iter() {
+            redacted = redacted.replace(secret.as_str(), REDACTED);
+        }
+    }
+    for pattern in SECRET_PATTERNS.iter() {
+        redacted = pattern.replace_all(&redacted, REDACTED).into_owned();
+    }
+    redacted
+}
+
+pub fn env_flag(name: &str) -> bool {
+    env::var(name).is_ok_and(|value| {
+        matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes"
+        )
+    })
+}
+
 pub fn normalize_workspace_dir(workspace_dir: &str) -> anyhow::Result<PathBuf> {
     let trimmed = workspace_dir.trim();
     if trimmed.is_empty() {
@@ -95,3 +224,170 @@ pub fn normalize_workspace_dir(workspace_dir: &str) -> anyhow::Result<PathBuf> {
     }
     Ok(PathBuf::from(trimmed))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT_TEST_DIR: AtomicU64 = AtomicU64::new(0);
+
+    fn temp_dir() -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "looper-settings-test-{}-{}",
+            std::process::id(),
+            NEXT_TEST_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn settings(model: &str) -> AgentSettings {
+        AgentSettings {
+            workspace_dir: "/work".to_string(),
+            port: 11000,
+            provider: "openai".to_string(),
+            model: model.to_string(),
+        }
+    }
+
+    fn keys() -> AgentKeys {
+        AgentKeys {
+            api_keys: vec![ProviderApiKey {
+                provider: "openai".to_string(),
+                api_key: "test-key-0123456789".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn interrupted_write_keeps_previous_config() {
+        let dir = temp_dir();
+        persist_config(&dir, settings("gpt-first"), keys()).unwrap();
+        fs::write(dir.join(".settings.json.tmp-1-0"), "{\"workspace_dir\":").unwrap();
+
+        let loaded = load_persisted_config(&dir).unwrap().unwrap();
+        assert_eq!(loaded.settings.model, "gpt-first");
+
+        persist_config(&dir, settings("gpt-second"), keys()).unwrap();
+        let loaded = load_persisted_config(&dir).unwrap().unwrap();
+        assert_eq!(loaded.settings.model, "gpt-second");
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn registered_key_is_masked_in_formatted_errors() {
+        register_secret("zq-registered-test-key-42");
+        let error = anyhow::anyhow!("provider rejected key zq-registered-test-key-42")
+            .context("model check failed");
+        let message = redact_secrets(&format!("{error:#}"));
+        assert_eq!(
+            message,
+            "model check failed: provider rejected key [redacted]"
+        );
+    }
+
+    #[test]
+    fn long_identifiers_are_not_redacted() {
+        let text = "session 3f2b8c1e-9a4d-4e7b-8c2f-1d5e6a7b8c9d at commit 4ff4758a1c2e3d4f5a6b7c8d9e0f1a2b3c4d5e6f";
+        assert_eq!(redact_secrets(text), text);
+        assert_eq!(
+            redact_secrets("Authorization: Bearer abcdefgh12345 and sk-abcdefgh1234"),
+            "Authorization: [redacted] and [redacted]"
+        );
+    }
+
+    fn bundle() -> ConfigBundle {
+        ConfigBundle {
+            version: CONFIG_BUNDLE_VERSION,
+            provider: "anthropic".to_string(),
+            model: "claude-test".to_string(),
+            plugins: vec![PluginOverride {
+                name: "http-fetch".to_string(),
+                enabled: false,
+            }],
+            keys_included: true,
+            api_keys: keys().api_keys,
+        }
+    }
+
+    #[test]
+    fn config_bundle_round_trips() {
+        let dir = temp_dir();
+        let path = dir.join("exports/bundle.json");
+        write_config_bundle(&path, &bundle()).unwrap();
+
+        let read = read_config_bundle(&path).unwrap();
+        assert_eq!(read.provider, "anthropic");
+        assert_eq!(read.model, "claude-test");
+        assert_eq!(read.plugins.len(), 1);
+        assert_eq!(read.plugins[0].name, "http-fetch");
+        assert!(!read.plugins[0].enabled);
+        assert!(read.keys_included);
+        assert_eq!(read.api_keys.len(), 1);
+        assert_eq!(read.api_keys[0].api_key, "test-key-0123456789");
+
+        let without_keys = ConfigBundle {
+            keys_included: false,
+            api_keys: Vec::new(),
+            ..bundle()
+        };
+        write_config_bundle(&path, &without_keys).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("api_keys"));
+        assert!(read_config_bundle(&path).unwrap().api_keys.is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn invalid_config_bundles_are_rejected() {
+        let dir = temp_dir();
+        let path = dir.join("bundle.json");
+        let duplicate = PluginOverride {
+            name: " http-fetch ".to_string(),
+            enabled: true,
+        };
+        let empty_key = ProviderApiKey {
+            provider: "openai".to_string(),
+            api_key: " ".to_string(),
+        };
+        for (bundle, message) in [
+            (
+                ConfigBundle {
+                    version: CONFIG_BUNDLE_VERSION + 1,
+                    ..bundle()
+                },
+                "is not supported",
+            ),
+            (
+                ConfigBundle {
+                    plugins: [bundle().plugins, vec![duplicate]].concat(),
+                    ..bundle()
+                },
+                "more than once",
+            ),
+            (
+                ConfigBundle {
+                    api_keys: vec![empty_key],
+                    ..bundle()
+                },
+                "empty provider or key",
+            ),
+            (
+                ConfigBundle {
+                    model: String::new(),
+                    ..bundle()
+                },
+                "empty model",
+            ),
+        ] {
+            write_config_bundle(&path, &bundle).unwrap();
+            let error = read_config_bundle(&path).unwrap_err().to_string();
+            assert!(error.contains(message), "{error}");
+        }
+
+        fs::write(&path, "{\"version\": 1").unwrap();
+        let error = read_config_bundle(&path).unwrap_err().to_string();
+        assert!(error.contains("invalid config bundle"), "{error}");
+        let _ = fs::remove_dir_all(dir);
+    }
+}
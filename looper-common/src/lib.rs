@@ -1,19 +1,29 @@
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 pub const DISCOVERY_HOST: &str = "127.0.0.1";
 pub const DISCOVERY_PORT: u16 = 10001;
 pub const DEFAULT_DISCOVERY_URL: &str = "ws://127.0.0.1:10001";
+pub const DISCOVERY_HEARTBEAT_INTERVAL_SECS: u64 = 10;
 
 pub const AGENT_HOST: &str = "127.0.0.1";
 pub const AGENT_PORT_START: u16 = 11000;
 pub const AGENT_PORT_END: u16 = 12000;
 
+static NEXT_TEMP_FILE_ID: AtomicU64 = AtomicU64::new(0);
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum AgentMode {
     Setup,
     Running,
+    Paused,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,6 +109,8 @@ pub enum AgentSocketMessage {
     },
     Error {
         message: String,
+        #[serde(default)]
+        code: ErrorCode,
     },
     SessionStart {
         origin: SessionOrigin,
@@ -120,27 +132,232 @@ pub enum AgentSocketMessage {
         success: bool,
         message: String,
     },
+    PluginStatuses {
+        plugins: Vec<PluginStatus>,
+    },
+    ExportEvents {
+        session_id: Option<String>,
+        format: ExportFormat,
+        path: String,
+    },
+    EventsExported {
+        path: String,
+        format: ExportFormat,
+        count: usize,
+    },
+    SearchChat {
+        query: String,
+        limit: usize,
+    },
+    ChatSearchResults {
+        query: String,
+        hits: Vec<ChatSearchHit>,
+    },
+    QueryAudit {
+        actuator: Option<String>,
+        since: Option<i64>,
+        until: Option<i64>,
+        limit: usize,
+    },
+    AuditEntries {
+        entries: Vec<AuditEntry>,
+    },
+    RemoveProviderKey {
+        provider: String,
+    },
+    ProviderKeyRemoved {
+        provider: String,
+        mode: AgentMode,
+    },
+    ExportConfig {
+        path: String,
+        include_keys: bool,
+    },
+    ConfigExported {
+        path: String,
+        keys_included: bool,
+    },
+    ImportConfig {
+        path: String,
+        include_keys: bool,
+    },
+    ConfigImported {
+        path: String,
+        mode: AgentMode,
+        keys_imported: usize,
+    },
+    CheckReadiness,
+    Readiness {
+        mode: AgentMode,
+        configured: bool,
+        store_ok: bool,
+        models_ok: Option<bool>,
+        message: String,
+        #[serde(default)]
+        uptime_secs: u64,
+        #[serde(default)]
+        restart_count: u64,
+        #[serde(default)]
+        first_started_at: i64,
+        #[serde(default)]
+        stalled_turns: u64,
+    },
+    CancelTurn {
+        session_id: String,
+    },
+    PauseAgent,
+    ResumeAgent,
+    StepAgent,
+    AgentStepped {
+        session_id: String,
+        remaining: usize,
+        #[serde(default)]
+        expired: usize,
+    },
+    ModeChanged {
+        mode: AgentMode,
+        #[serde(default)]
+        expired: usize,
+    },
+    PerceptQueued {
+        session_id: String,
+        queued: usize,
+        dropped: usize,
+    },
+    CheckModel,
+    QueryModelHistory {
+        limit: usize,
+    },
+    ModelHistory {
+        entries: Vec<ModelChange>,
+    },
+    ModelHealth {
+        provider: String,
+        model: String,
+        ok: bool,
+        message: String,
+    },
     PerceptObserved {
         session_id: String,
         domain: String,
         percept: Percept,
+        #[serde(default)]
+        priority: u8,
     },
     EffectApplied {
         session_id: String,
         domain: String,
         effect: Effect,
     },
+    PerceptBatch {
+        session_id: String,
+        domain: String,
+        percepts: Vec<Percept>,
+    },
+    PerceptBatchResult {
+        session_id: String,
+        queued: bool,
+        results: Vec<PerceptBatchItem>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "command", rename_all = "snake_case")]
 pub enum PluginCommandRequest {
-    Add { source: String },
-    Remove { plugin_name: String },
-    Enable { plugin_name: String },
-    Disable { plugin_name: String },
+    Add {
+        source: String,
+    },
+    Validate {
+        source: String,
+    },
+    Remove {
+        plugin_name: String,
+    },
+    Enable {
+        plugin_name: String,
+    },
+    Disable {
+        plugin_name: String,
+    },
+    SetEnv {
+        plugin_name: String,
+        key: String,
+        value: Option<String>,
+    },
     List,
     Catalog,
+    Reload,
+    Status,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginStatus {
+    pub name: String,
+    pub version: String,
+    pub source: String,
+    pub enabled: bool,
+    pub status_message: String,
+    pub actuators: Vec<String>,
+    pub sensors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSearchHit {
+    pub session_id: String,
+    pub turn_id: Option<String>,
+    pub role: Option<String>,
+    pub text: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerceptBatchItem {
+    pub index: usize,
+    pub accepted: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelChange {
+    pub provider: String,
+    pub model: String,
+    pub source: String,
+    pub changed_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub session_id: String,
+    pub turn_id: String,
+    pub action_id: String,
+    pub plugin: String,
+    pub actuator: String,
+    pub status: PlannedActionStatus,
+    pub deny_reason: Option<DenyReason>,
+    pub details: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidRequest,
+    SetupRequired,
+    NotConfigured,
+    SessionMismatch,
+    InvalidState,
+    NotFound,
+    Conflict,
+    LimitExceeded,
+    #[default]
+    Internal,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Jsonl,
+    Csv,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -152,7 +369,15 @@ pub enum SessionOrigin {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum Percept {
-    UserText { turn_id: String, text: String },
+    UserText {
+        turn_id: String,
+        text: String,
+    },
+    ActionFeedback {
+        turn_id: String,
+        text: String,
+        depth: u32,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -179,6 +404,17 @@ pub enum Effect {
         turn_id: String,
         action: PlannedAction,
     },
+    TurnTiming {
+        turn_id: String,
+        timing: TurnTiming,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TurnTiming {
+    pub plan_ms: u64,
+    pub action_ms: Vec<u64>,
+    pub model_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -201,6 +437,29 @@ pub struct PlannedAction {
     pub args: Value,
     pub status: PlannedActionStatus,
     pub details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deny_reason: Option<DenyReason>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DenyReason {
+    OutsideReadRoots,
+    OutsideWriteRoots,
+    WriteNeedsApproval,
+    UserDenied,
+    DependencyUnmet,
+    NoExecutor,
+    UnsupportedActuator,
+    ActionLimit,
+    CircuitOpen,
+    OutsideAllowedWindow,
+    ApprovalRequired,
+    HighRisk,
+    TimedOut,
+    OutputLimitExceeded,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -208,3 +467,61 @@ pub struct ProviderApiKey {
     pub provider: String,
     pub api_key: String,
 }
+
+pub fn write_file_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_file_atomic_with(path, |writer| writer.write_all(contents))
+}
+
+pub fn write_file_atomic_with<T, E>(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<fs::File>) -> Result<T, E>,
+) -> Result<T, E>
+where
+    E: From<io::Error>,
+{
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.tmp-{}-{}",
+        file_name.to_string_lossy(),
+        process::id(),
+        NEXT_TEMP_FILE_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)
+        .map_err(E::from)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            let value = write(&mut writer)?;
+            let file = writer
+                .into_inner()
+                .map_err(io::IntoInnerError::into_error)?;
+            file.sync_all()?;
+            fs::rename(&temp_path, path)?;
+            Ok(value)
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    let value = result?;
+    sync_parent_dir(path)?;
+    Ok(value)
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::File::open(parent)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
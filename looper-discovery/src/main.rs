@@ -4,12 +4,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, anyhow};
 use futures_util::{SinkExt, StreamExt};
 use looper_common::{
-    AGENT_PORT_END, AGENT_PORT_START, AgentEntry, AgentInfo, DISCOVERY_HOST, DISCOVERY_PORT,
-    DiscoveryRequest, DiscoveryResponse,
+    AGENT_PORT_END, AGENT_PORT_START, AgentEntry, AgentInfo, DISCOVERY_HEARTBEAT_INTERVAL_SECS,
+    DISCOVERY_HOST, DISCOVERY_PORT, DiscoveryRequest, DiscoveryResponse, write_file_atomic,
 };
 use serde::{Deserialize, Serialize};
 use tokio::net::{TcpListener, TcpStream};
@@ -17,6 +18,8 @@ use tokio::sync::Mutex;
 use tokio_tungstenite::{accept_async, tungstenite::Message};
 use uuid::Uuid;
 
+const HEARTBEAT_TIMEOUT_ENV: &str = "LOOPER_DISCOVERY_HEARTBEAT_TIMEOUT_SECS";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AgentLaunchConfig {
     workspace_dir: String,
@@ -177,6 +180,7 @@ async fn main() -> anyhow::Result<()> {
     let state = Arc::new(Mutex::new(DiscoveryState::from_launch_configs(
         launch_configs,
     )));
+    let heartbeat_timeout = heartbeat_timeout();
 
     loop {
         let (stream, addr) = listener
@@ -187,7 +191,9 @@ async fn main() -> anyhow::Result<()> {
         let state = Arc::clone(&state);
         let config_path = config_path.clone();
         tokio::spawn(async move {
-            if let Err(error) = handle_connection(stream, state, config_path).await {
+            if let Err(error) =
+                handle_connection(stream, state, config_path, heartbeat_timeout).await
+            {
                 eprintln!("connection {addr} failed: {error:#}");
             }
         });
@@ -198,6 +204,7 @@ async fn handle_connection(
     stream: TcpStream,
     state: Arc<Mutex<DiscoveryState>>,
     config_path: PathBuf,
+    heartbeat_timeout: Duration,
 ) -> anyhow::Result<()> {
     let ws_stream = accept_async(stream)
         .await
@@ -479,7 +486,22 @@ async fn handle_connection(
         }
     };
 
-    while let Some(message) = reader.next().await {
+    loop {
+        let message = match tokio::time::timeout(heartbeat_timeout, reader.next()).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(_) => {
+                if let Some(agent) = &registered_agent {
+                    eprintln!(
+                        "agent {} sent no heartbeat for {}s; evicting",
+                        agent.agent_id,
+                        heartbeat_timeout.as_secs()
+                    );
+                }
+                break;
+            }
+        };
+
         match message {
             Ok(Message::Close(_)) => break,
             Ok(_) => {}
@@ -500,6 +522,15 @@ async fn handle_connection(
     Ok(())
 }
 
+fn heartbeat_timeout() -> Duration {
+    let secs = env::var(HEARTBEAT_TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > DISCOVERY_HEARTBEAT_INTERVAL_SECS)
+        .unwrap_or(DISCOVERY_HEARTBEAT_INTERVAL_SECS * 3);
+    Duration::from_secs(secs)
+}
+
 fn agents_file_path() -> anyhow::Result<PathBuf> {
     let home = env::var("USERPROFILE")
         .or_else(|_| env::var("HOME"))
@@ -546,10 +577,7 @@ fn load_launch_configs(path: &PathBuf) -> anyhow::Result<Vec<AgentLaunchConfig>>
     Ok(configs)
 }
 
-fn persist_launch_configs(
-    path: &PathBuf,
-    launch_configs: &[AgentLaunchConfig],
-) -> anyhow::Result<()> {
+fn persist_launch_configs(path: &Path, launch_configs: &[AgentLaunchConfig]) -> anyhow::Result<()> {
     let parent = path
         .parent()
         .ok_or_else(|| anyhow!("invalid launch config path {}", path.display()))?;
@@ -561,7 +589,7 @@ fn persist_launch_configs(
         agents: launch_configs.to_vec(),
     };
     let text = serde_json::to_string_pretty(&file).context("failed to serialize launch config")?;
-    fs::write(path, text)
+    write_file_atomic(path, text.as_bytes())
         .with_context(|| format!("failed to write launch config file {}", path.display()))?;
     Ok(())
 }
@@ -679,3 +707,59 @@ fn discover_workspace_root(current_exe: &Path) -> Option<PathBuf> {
     let workspace_root = target_dir.parent()?;
     Some(workspace_root.to_path_buf())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use looper_common::AgentMode;
+    use tokio_tungstenite::connect_async;
+
+    #[tokio::test]
+    async fn silent_agent_is_evicted_and_its_port_released() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(DiscoveryState::from_launch_configs(Vec::new())));
+        let server_state = Arc::clone(&state);
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            handle_connection(
+                stream,
+                server_state,
+                env::temp_dir().join("looper-test-agents.json"),
+                Duration::from_millis(200),
+            )
+            .await
+        });
+
+        let (mut client, _) = connect_async(format!("ws://{addr}")).await.unwrap();
+        let register = DiscoveryRequest::Register {
+            agent_name: None,
+            requested_port: None,
+            workspace_dir: None,
+            mode: AgentMode::Running,
+        };
+        client
+            .send(Message::Text(serde_json::to_string(&register).unwrap()))
+            .await
+            .unwrap();
+        let Some(Ok(Message::Text(reply))) = client.next().await else {
+            panic!("expected a register response");
+        };
+        let DiscoveryResponse::Registered { assigned_port, .. } =
+            serde_json::from_str(&reply).unwrap()
+        else {
+            panic!("expected Registered, got {reply}");
+        };
+        assert_eq!(state.lock().await.agents.len(), 1);
+
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("silent agent was not evicted")
+            .unwrap()
+            .unwrap();
+        let mut state_guard = state.lock().await;
+        assert!(state_guard.agents.is_empty());
+        assert!(!state_guard.used_ports.contains(&assigned_port));
+        assert_eq!(state_guard.assign_port(), Some(assigned_port));
+    }
+}
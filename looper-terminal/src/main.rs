@@ -1,8 +1,10 @@
 use std::env;
+use std::fs;
 use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, bail};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
@@ -12,9 +14,11 @@ use crossterm::terminal::{
 };
 use futures_util::{SinkExt, StreamExt};
 use looper_common::{
-    AGENT_HOST, AgentEntry, AgentInfo, AgentMode, AgentSocketMessage, DEFAULT_DISCOVERY_URL,
-    DiscoveryRequest, DiscoveryResponse, Effect, Percept, PlannedAction, PlannedActionStatus,
-    PluginCommandRequest, ProviderApiKey, SessionOrigin,
+    AGENT_HOST, AgentEntry, AgentInfo, AgentMode, AgentSocketMessage, AuditEntry, ChatSearchHit,
+    DEFAULT_DISCOVERY_URL, DiscoveryRequest, DiscoveryResponse, Effect, ErrorCode, ExportFormat,
+    ModelChange, Percept, PerceptBatchItem, PlannedAction, PlannedActionStatus,
+    PluginCommandRequest, PluginStatus, ProviderApiKey, SessionOrigin, TurnTiming,
+    write_file_atomic,
 };
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
@@ -24,11 +28,18 @@ use ratatui::widgets::{Block, Padding, Paragraph, Wrap};
 use ratatui::{Frame, Terminal};
 use ratatui_widgets::list::{List, ListItem, ListState};
 use ratatui_widgets::scrollbar::{Scrollbar, ScrollbarOrientation, ScrollbarState};
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 const TICK_RATE: Duration = Duration::from_millis(450);
 const PROVIDERS: [&str; 3] = ["openai", "anthropic", "opencode-zen"];
+const DEFAULT_CHAT_HISTORY_LIMIT: usize = 200;
+const DEFAULT_PLANNING_HISTORY_LIMIT: usize = 200;
+const CHAT_SEARCH_LIMIT: usize = 20;
+const AUDIT_QUERY_LIMIT: usize = 50;
+const URGENT_PERCEPT_PRIORITY: u8 = 1;
+const MODEL_HISTORY_LIMIT: usize = 20;
 
 fn default_model_for_provider(provider: &str) -> &'static str {
     match provider {
@@ -58,14 +69,16 @@ async fn main() -> anyhow::Result<()> {
     };
 
     let mode = fetch_agent_mode(&runtime_agent).await?;
+    let check_model = mode == AgentMode::Setup;
     if mode == AgentMode::Setup {
         let Some(form) = run_setup_flow(&runtime_agent)? else {
             return Ok(());
         };
         submit_setup(&runtime_agent, form).await?;
+        clear_setup_draft(runtime_agent.assigned_port);
     }
 
-    run_chat_ui(&runtime_agent)
+    run_chat_ui(&runtime_agent, check_model)
 }
 
 async fn discover_agents() -> anyhow::Result<Vec<AgentEntry>> {
@@ -394,10 +407,16 @@ fn run_setup_flow(agent: &AgentInfo) -> anyhow::Result<Option<SetupForm>> {
         cursor_visible: true,
         agent_port: agent.assigned_port,
     };
+    if let Some(draft) = load_setup_draft(agent.assigned_port) {
+        apply_setup_draft(&mut app, draft);
+    }
 
     run_tui_loop(&mut app, draw_setup, handle_setup_key)?;
 
     if app.stage != SetupStage::Done {
+        if let Err(error) = save_setup_draft(&app) {
+            eprintln!("failed to save setup progress: {error:#}");
+        }
         return Ok(None);
     }
 
@@ -409,6 +428,98 @@ fn run_setup_flow(agent: &AgentInfo) -> anyhow::Result<Option<SetupForm>> {
     }))
 }
 
+#[derive(Serialize, Deserialize)]
+struct SetupDraft {
+    stage: SetupStage,
+    workspace_dir: String,
+    provider: String,
+    model: String,
+}
+
+fn setup_draft_path(agent_port: u16) -> anyhow::Result<PathBuf> {
+    let home = env::var("USERPROFILE")
+        .or_else(|_| env::var("HOME"))
+        .map(PathBuf::from)
+        .context("failed to resolve USERPROFILE/HOME for setup draft")?;
+    Ok(home
+        .join(".looper")
+        .join(format!("setup-draft-{agent_port}.json")))
+}
+
+fn load_setup_draft(agent_port: u16) -> Option<SetupDraft> {
+    let path = setup_draft_path(agent_port).ok()?;
+    let text = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<SetupDraft>(&text) {
+        Ok(draft) => Some(draft),
+        Err(_) => {
+            let _ = fs::remove_file(&path);
+            None
+        }
+    }
+}
+
+fn apply_setup_draft(app: &mut SetupApp, draft: SetupDraft) {
+    app.workspace_input = draft.workspace_dir.trim().to_string();
+    if draft.stage == SetupStage::Workspace
+        || app.workspace_input.is_empty()
+        || !PathBuf::from(&app.workspace_input).is_dir()
+    {
+        app.stage = SetupStage::Workspace;
+        return;
+    }
+
+    let Some(provider_index) = PROVIDERS.iter().position(|p| *p == draft.provider) else {
+        app.stage = SetupStage::Provider;
+        return;
+    };
+    app.provider_index = provider_index;
+    app.model_input = draft.model.trim().to_string();
+    if app.model_input.is_empty() {
+        app.model_input = default_model_for_provider(PROVIDERS[provider_index]).to_string();
+    }
+
+    app.stage = match draft.stage {
+        SetupStage::Provider => SetupStage::Provider,
+        SetupStage::Model => SetupStage::Model,
+        _ if draft.model.trim().is_empty() => SetupStage::Model,
+        _ => SetupStage::ApiKey,
+    };
+}
+
+fn setup_draft(app: &SetupApp) -> SetupDraft {
+    SetupDraft {
+        stage: app.stage,
+        workspace_dir: app.workspace_input.trim().to_string(),
+        provider: PROVIDERS[app.provider_index].to_string(),
+        model: app.model_input.trim().to_string(),
+    }
+}
+
+fn save_setup_draft(app: &SetupApp) -> anyhow::Result<()> {
+    if app.stage == SetupStage::Workspace && app.workspace_input.trim().is_empty() {
+        clear_setup_draft(app.agent_port);
+        return Ok(());
+    }
+
+    let path = setup_draft_path(app.agent_port)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+
+    let text = serde_json::to_string_pretty(&setup_draft(app))
+        .context("failed to serialize setup draft")?;
+    write_file_atomic(&path, text.as_bytes())
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+fn clear_setup_draft(agent_port: u16) {
+    if let Ok(path) = setup_draft_path(agent_port) {
+        let _ = fs::remove_file(path);
+    }
+}
+
 async fn submit_setup(agent: &AgentInfo, form: SetupForm) -> anyhow::Result<()> {
     let url = format!("ws://{AGENT_HOST}:{}", agent.assigned_port);
     let (ws_stream, _) = connect_async(&url)
@@ -462,8 +573,8 @@ async fn submit_setup(agent: &AgentInfo, form: SetupForm) -> anyhow::Result<()>
                         writer.send(Message::Close(None)).await.ok();
                         return Ok(());
                     }
-                    AgentSocketMessage::Error { message } => {
-                        bail!("setup failed: {message}");
+                    AgentSocketMessage::Error { message, code } => {
+                        bail!("setup failed: {}", format_agent_error(code, &message));
                     }
                     _ => {}
                 }
@@ -477,7 +588,7 @@ async fn submit_setup(agent: &AgentInfo, form: SetupForm) -> anyhow::Result<()>
     bail!("agent disconnected before setup confirmation")
 }
 
-fn run_chat_ui(agent: &AgentInfo) -> anyhow::Result<()> {
+fn run_chat_ui(agent: &AgentInfo, check_model: bool) -> anyhow::Result<()> {
     let connection_state = Arc::new(AtomicBool::new(false));
     let monitor_state = Arc::clone(&connection_state);
     let agent_port = agent.assigned_port;
@@ -516,11 +627,25 @@ fn run_chat_ui(agent: &AgentInfo) -> anyhow::Result<()> {
         chat_event_rx,
         session_id: None,
         next_turn_id: 1,
+        assistant_turn_id: None,
         active_provider: "(pending)".to_string(),
         active_model: "(pending)".to_string(),
         agent_port: agent.assigned_port,
         planned_actions: Vec::new(),
+        last_turn_timing: None,
+        history_limit: history_limit_from_env(
+            "LOOPER_CHAT_HISTORY_LIMIT",
+            DEFAULT_CHAT_HISTORY_LIMIT,
+        ),
+        planning_limit: history_limit_from_env(
+            "LOOPER_PLANNING_HISTORY_LIMIT",
+            DEFAULT_PLANNING_HISTORY_LIMIT,
+        ),
+        plugin_view: None,
     };
+    if check_model {
+        let _ = app.chat_cmd_tx.send(ChatCommand::CheckModel);
+    }
 
     let result = run_tui_loop(&mut app, draw_chat, handle_chat_key);
     let _ = app.chat_cmd_tx.send(ChatCommand::EndSession);
@@ -531,8 +656,48 @@ fn run_chat_ui(agent: &AgentInfo) -> anyhow::Result<()> {
 
 #[derive(Debug)]
 enum ChatCommand {
-    SendPercept { turn_id: String, text: String },
-    PluginCommand { command: PluginCommandRequest },
+    SendPercept {
+        turn_id: String,
+        text: String,
+        priority: u8,
+    },
+    PluginCommand {
+        command: PluginCommandRequest,
+    },
+    ExportEvents {
+        all_sessions: bool,
+        format: ExportFormat,
+        path: String,
+    },
+    SearchChat {
+        query: String,
+    },
+    ReplayFile {
+        path: String,
+    },
+    QueryAudit {
+        actuator: Option<String>,
+        since_secs: Option<u64>,
+    },
+    CheckModel,
+    ModelHistory,
+    RemoveProviderKey {
+        provider: String,
+    },
+    ExportConfig {
+        path: String,
+        include_keys: bool,
+    },
+    ImportConfig {
+        path: String,
+        include_keys: bool,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    StepAgent,
+    CheckReadiness,
+    CancelTurn,
     EndSession,
 }
 
@@ -553,6 +718,66 @@ enum ChatEvent {
         success: bool,
         message: String,
     },
+    PluginStatuses {
+        plugins: Vec<PluginStatus>,
+    },
+    EventsExported {
+        path: String,
+        count: usize,
+    },
+    ChatSearchResults {
+        query: String,
+        hits: Vec<ChatSearchHit>,
+    },
+    AuditEntries {
+        entries: Vec<AuditEntry>,
+    },
+    PerceptBatchResult {
+        queued: bool,
+        results: Vec<PerceptBatchItem>,
+    },
+    ModelHistory {
+        entries: Vec<ModelChange>,
+    },
+    ModelHealth {
+        provider: String,
+        model: String,
+        ok: bool,
+        message: String,
+    },
+    ProviderKeyRemoved {
+        provider: String,
+        mode: AgentMode,
+    },
+    ConfigExported {
+        path: String,
+        keys_included: bool,
+    },
+    ConfigImported {
+        path: String,
+        mode: AgentMode,
+        keys_imported: usize,
+    },
+    ModeChanged {
+        mode: AgentMode,
+        expired: usize,
+    },
+    PerceptQueued {
+        queued: usize,
+        dropped: usize,
+    },
+    AgentStepped {
+        remaining: usize,
+        expired: usize,
+    },
+    Readiness {
+        store_ok: bool,
+        models_ok: Option<bool>,
+        message: String,
+        uptime_secs: u64,
+        restart_count: u64,
+        stalled_turns: u64,
+    },
     Disconnected,
 }
 
@@ -590,7 +815,7 @@ async fn chat_socket_loop(
                 };
                 match payload {
                     AgentSocketMessage::AgentHello { mode, .. } => {
-                        if mode != AgentMode::Running {
+                        if mode == AgentMode::Setup {
                             let _ = event_tx.send(ChatEvent::Error {
                                 message: "agent is not in running mode".to_string(),
                             });
@@ -631,8 +856,10 @@ async fn chat_socket_loop(
                         });
                         break;
                     }
-                    AgentSocketMessage::Error { message } => {
-                        let _ = event_tx.send(ChatEvent::Error { message });
+                    AgentSocketMessage::Error { message, code } => {
+                        let _ = event_tx.send(ChatEvent::Error {
+                            message: format_agent_error(code, &message),
+                        });
                     }
                     _ => {}
                 }
@@ -661,7 +888,11 @@ async fn chat_socket_loop(
                 };
 
                 match cmd {
-                    ChatCommand::SendPercept { turn_id, text } => {
+                    ChatCommand::SendPercept {
+                        turn_id,
+                        text,
+                        priority,
+                    } => {
                         let Some(active_session_id) = session_id.clone() else {
                             let _ = event_tx.send(ChatEvent::Error { message: "session is not started".to_string() });
                             continue;
@@ -670,6 +901,7 @@ async fn chat_socket_loop(
                             session_id: active_session_id,
                             domain: "chat".to_string(),
                             percept: Percept::UserText { turn_id, text },
+                            priority,
                         };
                         if let Err(error) = writer
                             .send(Message::Text(
@@ -701,6 +933,272 @@ async fn chat_socket_loop(
                             break;
                         }
                     }
+                    ChatCommand::ExportEvents {
+                        all_sessions,
+                        format,
+                        path,
+                    } => {
+                        let export_session_id = if all_sessions {
+                            None
+                        } else {
+                            let Some(active_session_id) = session_id.clone() else {
+                                let _ = event_tx.send(ChatEvent::Error {
+                                    message: "no active session to export".to_string(),
+                                });
+                                continue;
+                            };
+                            Some(active_session_id)
+                        };
+                        let request = AgentSocketMessage::ExportEvents {
+                            session_id: export_session_id,
+                            format,
+                            path,
+                        };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send export request: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::SearchChat { query } => {
+                        let request = AgentSocketMessage::SearchChat {
+                            query,
+                            limit: CHAT_SEARCH_LIMIT,
+                        };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send search request: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::ReplayFile { path } => {
+                        let Some(active_session_id) = session_id.clone() else {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: "session is not started".to_string(),
+                            });
+                            continue;
+                        };
+                        let contents = match fs::read_to_string(&path) {
+                            Ok(contents) => contents,
+                            Err(error) => {
+                                let _ = event_tx.send(ChatEvent::Error {
+                                    message: format!("failed to read {path}: {error}"),
+                                });
+                                continue;
+                            }
+                        };
+                        let started = now_millis();
+                        let percepts = contents
+                            .lines()
+                            .map(str::trim)
+                            .filter(|line| !line.is_empty())
+                            .enumerate()
+                            .map(|(index, line)| Percept::UserText {
+                                turn_id: format!("replay-{started}-{}", index + 1),
+                                text: line.to_string(),
+                            })
+                            .collect::<Vec<_>>();
+                        let request = AgentSocketMessage::PerceptBatch {
+                            session_id: active_session_id,
+                            domain: "chat".to_string(),
+                            percepts,
+                        };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send percept batch: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::QueryAudit {
+                        actuator,
+                        since_secs,
+                    } => {
+                        let request = AgentSocketMessage::QueryAudit {
+                            actuator,
+                            since: since_secs
+                                .map(|secs| now_millis() - (secs as i64).saturating_mul(1000)),
+                            until: None,
+                            limit: AUDIT_QUERY_LIMIT,
+                        };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send audit request: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::RemoveProviderKey { provider } => {
+                        let request = AgentSocketMessage::RemoveProviderKey { provider };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send provider key removal: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::ExportConfig { path, include_keys } => {
+                        let request = AgentSocketMessage::ExportConfig { path, include_keys };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send config export request: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::ImportConfig { path, include_keys } => {
+                        let request = AgentSocketMessage::ImportConfig { path, include_keys };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send config import request: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::SetPaused { paused } => {
+                        let request = if paused {
+                            AgentSocketMessage::PauseAgent
+                        } else {
+                            AgentSocketMessage::ResumeAgent
+                        };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send pause request: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::StepAgent => {
+                        let request = AgentSocketMessage::StepAgent;
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send step request: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::CancelTurn => {
+                        let Some(active_session_id) = session_id.clone() else {
+                            let _ = event_tx.send(ChatEvent::Error { message: "session is not started".to_string() });
+                            continue;
+                        };
+                        let request = AgentSocketMessage::CancelTurn {
+                            session_id: active_session_id,
+                        };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send cancel request: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::CheckReadiness => {
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&AgentSocketMessage::CheckReadiness)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send readiness check: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::CheckModel => {
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&AgentSocketMessage::CheckModel)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send model check: {error}"),
+                            });
+                            break;
+                        }
+                    }
+                    ChatCommand::ModelHistory => {
+                        let request = AgentSocketMessage::QueryModelHistory {
+                            limit: MODEL_HISTORY_LIMIT,
+                        };
+                        if let Err(error) = writer
+                            .send(Message::Text(
+                                serde_json::to_string(&request)
+                                    .unwrap_or_else(|_| "{}".to_string()),
+                            ))
+                            .await
+                        {
+                            let _ = event_tx.send(ChatEvent::Error {
+                                message: format!("failed to send model history request: {error}"),
+                            });
+                            break;
+                        }
+                    }
                     ChatCommand::EndSession => {
                         if let Some(active_session_id) = session_id.clone() {
                             let end = AgentSocketMessage::SessionEnd {
@@ -738,6 +1236,11 @@ async fn chat_socket_loop(
                             AgentSocketMessage::EffectApplied { effect, .. } => {
                                 let _ = event_tx.send(ChatEvent::EffectApplied { effect });
                             }
+                            AgentSocketMessage::PluginCommandResult {
+                                command: PluginCommandRequest::Status,
+                                success: true,
+                                ..
+                            } => {}
                             AgentSocketMessage::PluginCommandResult {
                                 success, message, ..
                             } => {
@@ -746,8 +1249,101 @@ async fn chat_socket_loop(
                                     message,
                                 });
                             }
-                            AgentSocketMessage::Error { message } => {
-                                let _ = event_tx.send(ChatEvent::Error { message });
+                            AgentSocketMessage::PluginStatuses { plugins } => {
+                                let _ = event_tx.send(ChatEvent::PluginStatuses { plugins });
+                            }
+                            AgentSocketMessage::EventsExported { path, count, .. } => {
+                                let _ = event_tx.send(ChatEvent::EventsExported { path, count });
+                            }
+                            AgentSocketMessage::ProviderKeyRemoved { provider, mode } => {
+                                let _ =
+                                    event_tx.send(ChatEvent::ProviderKeyRemoved { provider, mode });
+                            }
+                            AgentSocketMessage::ConfigExported {
+                                path,
+                                keys_included,
+                            } => {
+                                let _ = event_tx.send(ChatEvent::ConfigExported {
+                                    path,
+                                    keys_included,
+                                });
+                            }
+                            AgentSocketMessage::ConfigImported {
+                                path,
+                                mode,
+                                keys_imported,
+                            } => {
+                                let _ = event_tx.send(ChatEvent::ConfigImported {
+                                    path,
+                                    mode,
+                                    keys_imported,
+                                });
+                            }
+                            AgentSocketMessage::Readiness {
+                                store_ok,
+                                models_ok,
+                                message,
+                                uptime_secs,
+                                restart_count,
+                                stalled_turns,
+                                ..
+                            } => {
+                                let _ = event_tx.send(ChatEvent::Readiness {
+                                    store_ok,
+                                    models_ok,
+                                    message,
+                                    uptime_secs,
+                                    restart_count,
+                                    stalled_turns,
+                                });
+                            }
+                            AgentSocketMessage::ModeChanged { mode, expired } => {
+                                let _ = event_tx.send(ChatEvent::ModeChanged { mode, expired });
+                            }
+                            AgentSocketMessage::PerceptQueued {
+                                queued, dropped, ..
+                            } => {
+                                let _ = event_tx.send(ChatEvent::PerceptQueued { queued, dropped });
+                            }
+                            AgentSocketMessage::AgentStepped {
+                                remaining, expired, ..
+                            } => {
+                                let _ =
+                                    event_tx.send(ChatEvent::AgentStepped { remaining, expired });
+                            }
+                            AgentSocketMessage::ModelHealth {
+                                provider,
+                                model,
+                                ok,
+                                message,
+                            } => {
+                                let _ = event_tx.send(ChatEvent::ModelHealth {
+                                    provider,
+                                    model,
+                                    ok,
+                                    message,
+                                });
+                            }
+                            AgentSocketMessage::ChatSearchResults { query, hits } => {
+                                let _ =
+                                    event_tx.send(ChatEvent::ChatSearchResults { query, hits });
+                            }
+                            AgentSocketMessage::AuditEntries { entries } => {
+                                let _ = event_tx.send(ChatEvent::AuditEntries { entries });
+                            }
+                            AgentSocketMessage::PerceptBatchResult {
+                                queued, results, ..
+                            } => {
+                                let _ =
+                                    event_tx.send(ChatEvent::PerceptBatchResult { queued, results });
+                            }
+                            AgentSocketMessage::ModelHistory { entries } => {
+                                let _ = event_tx.send(ChatEvent::ModelHistory { entries });
+                            }
+                            AgentSocketMessage::Error { message, code } => {
+                                let _ = event_tx.send(ChatEvent::Error {
+                                    message: format_agent_error(code, &message),
+                                });
                             }
                             _ => {}
                         }
@@ -1005,7 +1601,8 @@ fn draw_agent_selector(frame: &mut Frame, app: &mut AgentSelectorApp) {
     frame.render_widget(help, chunks[2]);
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 enum SetupStage {
     Workspace,
     Provider,
@@ -1403,10 +2000,47 @@ struct ChatApp {
     chat_event_rx: UnboundedReceiver<ChatEvent>,
     session_id: Option<String>,
     next_turn_id: u64,
+    assistant_turn_id: Option<String>,
     active_provider: String,
     active_model: String,
     agent_port: u16,
     planned_actions: Vec<PlannedAction>,
+    last_turn_timing: Option<TurnTiming>,
+    history_limit: usize,
+    planning_limit: usize,
+    plugin_view: Option<PluginView>,
+}
+
+struct PluginView {
+    plugins: Vec<PluginStatus>,
+    selected: usize,
+}
+
+impl ChatApp {
+    fn push_message(&mut self, message: ChatMessage) {
+        self.messages.push(message);
+        trim_to_limit(&mut self.messages, self.history_limit);
+    }
+
+    fn push_planned_action(&mut self, action: PlannedAction) {
+        self.planned_actions.push(action);
+        trim_to_limit(&mut self.planned_actions, self.planning_limit);
+    }
+}
+
+fn trim_to_limit<T>(entries: &mut Vec<T>, limit: usize) {
+    if entries.len() > limit {
+        let overflow = entries.len() - limit;
+        entries.drain(..overflow);
+    }
+}
+
+fn history_limit_from_env(var_name: &str, default_limit: usize) -> usize {
+    env::var(var_name)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(default_limit)
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -1482,7 +2116,7 @@ impl TuiApp for ChatApp {
                     self.active_provider = provider;
                     self.active_model = model;
                     self.planned_actions.clear();
-                    self.messages.push(ChatMessage {
+                    self.push_message(ChatMessage {
                         role: MessageRole::System,
                         text: format!(
                             "Connected to {session_id} on ws://{AGENT_HOST}:{}",
@@ -1494,40 +2128,57 @@ impl TuiApp for ChatApp {
                 }
                 ChatEvent::EffectApplied { effect } => {
                     match effect {
-                        Effect::ChatResponseDelta { text_delta, .. } => {
+                        Effect::ChatResponseDelta {
+                            turn_id,
+                            text_delta,
+                        } => {
+                            let same_turn =
+                                self.assistant_turn_id.as_deref() == Some(turn_id.as_str());
                             match self.messages.last_mut() {
-                                Some(last) if last.role == MessageRole::Assistant => {
+                                Some(last) if last.role == MessageRole::Assistant && same_turn => {
                                     last.text.push_str(&text_delta);
                                 }
                                 _ => {
-                                    self.messages.push(ChatMessage {
+                                    self.push_message(ChatMessage {
                                         role: MessageRole::Assistant,
                                         text: text_delta,
                                     });
                                 }
                             }
+                            self.assistant_turn_id = Some(turn_id);
                         }
-                        Effect::ChatResponse { text, .. } => match self.messages.last_mut() {
-                            Some(last)
-                                if last.role == MessageRole::Assistant && !last.text.is_empty() =>
-                            {
-                                last.text = text;
+                        Effect::ChatResponse { turn_id, text } => {
+                            let same_turn =
+                                self.assistant_turn_id.as_deref() == Some(turn_id.as_str());
+                            match self.messages.last_mut() {
+                                Some(last)
+                                    if last.role == MessageRole::Assistant
+                                        && !last.text.is_empty()
+                                        && same_turn =>
+                                {
+                                    last.text = text;
+                                }
+                                _ => self.push_message(ChatMessage {
+                                    role: MessageRole::Assistant,
+                                    text,
+                                }),
                             }
-                            _ => self.messages.push(ChatMessage {
-                                role: MessageRole::Assistant,
-                                text,
-                            }),
-                        },
+                            self.assistant_turn_id = Some(turn_id);
+                        }
                         Effect::TaskCompletion {
                             status, details, ..
                         } => {
-                            self.messages.push(ChatMessage {
+                            self.push_message(ChatMessage {
                                 role: MessageRole::System,
                                 text: format!("Task Completion: {status} - {details}"),
                             });
                         }
                         Effect::PlanUpdated { actions, .. } => {
                             self.planned_actions = actions;
+                            trim_to_limit(&mut self.planned_actions, self.planning_limit);
+                        }
+                        Effect::TurnTiming { timing, .. } => {
+                            self.last_turn_timing = Some(timing);
                         }
                         Effect::ActionStatusChanged { action, .. } => {
                             if let Some(existing) = self
@@ -1537,7 +2188,7 @@ impl TuiApp for ChatApp {
                             {
                                 *existing = action;
                             } else {
-                                self.planned_actions.push(action);
+                                self.push_planned_action(action);
                             }
                         }
                     }
@@ -1545,7 +2196,7 @@ impl TuiApp for ChatApp {
                     self.status_ticks = 0;
                 }
                 ChatEvent::Error { message } => {
-                    self.messages.push(ChatMessage {
+                    self.push_message(ChatMessage {
                         role: MessageRole::System,
                         text: format!("Error: {message}"),
                     });
@@ -1553,7 +2204,7 @@ impl TuiApp for ChatApp {
                     self.status_ticks = 0;
                 }
                 ChatEvent::PluginCommandResult { success, message } => {
-                    self.messages.push(ChatMessage {
+                    self.push_message(ChatMessage {
                         role: MessageRole::System,
                         text: if success {
                             format!("Plugin: {message}")
@@ -1564,8 +2215,212 @@ impl TuiApp for ChatApp {
                     self.status = ChatStatus::Idle;
                     self.status_ticks = 0;
                 }
+                ChatEvent::PluginStatuses { plugins } => {
+                    let selected = self
+                        .plugin_view
+                        .as_ref()
+                        .map(|view| view.selected)
+                        .unwrap_or(0)
+                        .min(plugins.len().saturating_sub(1));
+                    self.plugin_view = Some(PluginView { plugins, selected });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::EventsExported { path, count } => {
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: format!("Exported {count} events to {path}"),
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::ModelHealth {
+                    provider,
+                    model,
+                    ok,
+                    message,
+                } => {
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: if ok {
+                            format!("Model check: {provider}/{model} is reachable")
+                        } else {
+                            format!("Model check failed for {provider}/{model}: {message}")
+                        },
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::ProviderKeyRemoved { provider, mode } => {
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: if mode == AgentMode::Setup {
+                            format!(
+                                "Removed API key for {provider}. It was the active provider, so the agent returned to setup mode."
+                            )
+                        } else {
+                            format!("Removed API key for {provider}.")
+                        },
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::ConfigExported {
+                    path,
+                    keys_included,
+                } => {
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: if keys_included {
+                            format!("Exported config bundle with API keys to {path}")
+                        } else {
+                            format!("Exported config bundle to {path} (API keys omitted)")
+                        },
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::ConfigImported {
+                    path,
+                    mode,
+                    keys_imported,
+                } => {
+                    let mut text =
+                        format!("Imported config bundle from {path} ({keys_imported} API keys).");
+                    if mode == AgentMode::Setup {
+                        text.push_str(
+                            " The active provider has no API key, so the agent returned to setup mode.",
+                        );
+                    } else {
+                        text.push_str(" New sessions use the imported provider and model.");
+                    }
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text,
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::Readiness {
+                    store_ok,
+                    models_ok,
+                    message,
+                    uptime_secs,
+                    restart_count,
+                    stalled_turns,
+                } => {
+                    let models = match models_ok {
+                        Some(true) => "ok",
+                        Some(false) => "failing",
+                        None => "unchecked",
+                    };
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: format!(
+                            "Agent status: {message} (store: {}, model: {models}, uptime: {}, restarts: {restart_count}, stalled turns: {stalled_turns})",
+                            if store_ok { "ok" } else { "failing" },
+                            format_uptime(uptime_secs)
+                        ),
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::ModeChanged { mode, expired } => {
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: if mode == AgentMode::Paused {
+                            "Agent paused. Messages are queued until /resume.".to_string()
+                        } else if expired > 0 {
+                            format!(
+                                "Agent resumed. {expired} queued messages expired and were dropped."
+                            )
+                        } else {
+                            "Agent resumed.".to_string()
+                        },
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::PerceptQueued { queued, dropped } => {
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: if dropped > 0 {
+                            format!(
+                                "Agent is paused; message queued ({queued} pending, {dropped} dropped because the queue is full)."
+                            )
+                        } else {
+                            format!("Agent is paused; message queued ({queued} pending).")
+                        },
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::AgentStepped { remaining, expired } => {
+                    let expired = if expired > 0 {
+                        format!(", {expired} expired and dropped")
+                    } else {
+                        String::new()
+                    };
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: format!(
+                            "Running one queued message; the agent stays paused ({remaining} still queued{expired})."
+                        ),
+                    });
+                }
+                ChatEvent::ChatSearchResults { query, hits } => {
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: format_search_results(&query, &hits),
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::AuditEntries { entries } => {
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: format_audit_entries(&entries),
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
+                ChatEvent::PerceptBatchResult { queued, results } => {
+                    let accepted = results.iter().filter(|item| item.accepted).count();
+                    let mut lines = vec![format!(
+                        "Replay: {accepted} of {} messages {}.",
+                        results.len(),
+                        if queued {
+                            "queued until /resume"
+                        } else {
+                            "accepted"
+                        }
+                    )];
+                    lines.extend(results.iter().filter(|item| !item.accepted).map(|item| {
+                        format!(
+                            "- message {} rejected: {}",
+                            item.index + 1,
+                            item.message.as_deref().unwrap_or("unknown reason")
+                        )
+                    }));
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: lines.join("\n"),
+                    });
+                    if queued || accepted == 0 {
+                        self.status = ChatStatus::Idle;
+                        self.status_ticks = 0;
+                    }
+                }
+                ChatEvent::ModelHistory { entries } => {
+                    self.push_message(ChatMessage {
+                        role: MessageRole::System,
+                        text: format_model_history(&entries),
+                    });
+                    self.status = ChatStatus::Idle;
+                    self.status_ticks = 0;
+                }
                 ChatEvent::Disconnected => {
-                    self.messages.push(ChatMessage {
+                    self.push_message(ChatMessage {
                         role: MessageRole::System,
                         text: "Connection closed.".to_string(),
                     });
@@ -1578,6 +2433,13 @@ impl TuiApp for ChatApp {
 }
 
 fn handle_chat_key(app: &mut ChatApp, key: KeyEvent) {
+    if app.plugin_view.is_some()
+        && !(key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+    {
+        handle_plugin_view_key(app, key);
+        return;
+    }
+
     if matches!(key.code, KeyCode::Esc)
         || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
     {
@@ -1598,7 +2460,7 @@ fn handle_chat_key(app: &mut ChatApp, key: KeyEvent) {
             let text = app.input.trim().to_string();
 
             if let Some(command) = parse_plugin_command(&text) {
-                app.messages.push(ChatMessage {
+                app.push_message(ChatMessage {
                     role: MessageRole::User,
                     text: text.clone(),
                 });
@@ -1609,7 +2471,7 @@ fn handle_chat_key(app: &mut ChatApp, key: KeyEvent) {
                             .send(ChatCommand::PluginCommand { command })
                             .is_err()
                         {
-                            app.messages.push(ChatMessage {
+                            app.push_message(ChatMessage {
                                 role: MessageRole::System,
                                 text: "Error: Unable to send plugin command to agent.".to_string(),
                             });
@@ -1619,7 +2481,7 @@ fn handle_chat_key(app: &mut ChatApp, key: KeyEvent) {
                         }
                     }
                     Err(error_message) => {
-                        app.messages.push(ChatMessage {
+                        app.push_message(ChatMessage {
                             role: MessageRole::System,
                             text: error_message,
                         });
@@ -1633,18 +2495,67 @@ fn handle_chat_key(app: &mut ChatApp, key: KeyEvent) {
                 return;
             }
 
+            if let Some(command) = parse_export_command(&text)
+                .or_else(|| parse_search_command(&text))
+                .or_else(|| parse_audit_command(&text))
+                .or_else(|| parse_replay_command(&text))
+                .or_else(|| parse_model_command(&text))
+                .or_else(|| parse_provider_command(&text))
+                .or_else(|| parse_config_command(&text))
+                .or_else(|| parse_pause_command(&text))
+                .or_else(|| parse_agent_command(&text))
+            {
+                app.push_message(ChatMessage {
+                    role: MessageRole::User,
+                    text: text.clone(),
+                });
+                match command {
+                    Ok(command) => {
+                        if app.chat_cmd_tx.send(command).is_err() {
+                            app.push_message(ChatMessage {
+                                role: MessageRole::System,
+                                text: "Error: Unable to send request to agent.".to_string(),
+                            });
+                        } else {
+                            app.status = ChatStatus::Thinking;
+                            app.status_ticks = 0;
+                        }
+                    }
+                    Err(error_message) => {
+                        app.push_message(ChatMessage {
+                            role: MessageRole::System,
+                            text: error_message,
+                        });
+                    }
+                }
+                app.input.clear();
+                app.input_view_backscroll = 0;
+                app.follow_tail = true;
+                return;
+            }
+
             let turn_id = format!("turn-{}", app.next_turn_id);
             app.next_turn_id = app.next_turn_id.saturating_add(1);
-            app.messages.push(ChatMessage {
+            app.push_message(ChatMessage {
                 role: MessageRole::User,
                 text: text.clone(),
             });
+            let (priority, text) = match text.strip_prefix("/urgent ") {
+                Some(rest) if !rest.trim().is_empty() => {
+                    (URGENT_PERCEPT_PRIORITY, rest.trim().to_string())
+                }
+                _ => (0, text),
+            };
             if app
                 .chat_cmd_tx
-                .send(ChatCommand::SendPercept { turn_id, text })
+                .send(ChatCommand::SendPercept {
+                    turn_id,
+                    text,
+                    priority,
+                })
                 .is_err()
             {
-                app.messages.push(ChatMessage {
+                app.push_message(ChatMessage {
                     role: MessageRole::System,
                     text: "Error: Unable to send percept to agent.".to_string(),
                 });
@@ -1685,8 +2596,53 @@ fn handle_chat_key(app: &mut ChatApp, key: KeyEvent) {
             if !key.modifiers.contains(KeyModifiers::CONTROL)
                 && !key.modifiers.contains(KeyModifiers::ALT)
             {
-                app.input.push(c);
-                app.input_view_backscroll = 0;
+                app.input.push(c);
+                app.input_view_backscroll = 0;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_plugin_view_key(app: &mut ChatApp, key: KeyEvent) {
+    let Some(view) = app.plugin_view.as_mut() else {
+        return;
+    };
+
+    match key.code {
+        KeyCode::Esc => app.plugin_view = None,
+        KeyCode::Up => view.selected = view.selected.saturating_sub(1),
+        KeyCode::Down if view.selected + 1 < view.plugins.len() => view.selected += 1,
+        KeyCode::Enter | KeyCode::Char(' ') => {
+            let Some(plugin) = view.plugins.get(view.selected) else {
+                return;
+            };
+            let plugin_name = plugin.name.clone();
+            let command = if plugin.enabled {
+                PluginCommandRequest::Disable { plugin_name }
+            } else {
+                PluginCommandRequest::Enable { plugin_name }
+            };
+
+            if app
+                .chat_cmd_tx
+                .send(ChatCommand::PluginCommand { command })
+                .is_err()
+                || app
+                    .chat_cmd_tx
+                    .send(ChatCommand::PluginCommand {
+                        command: PluginCommandRequest::Status,
+                    })
+                    .is_err()
+            {
+                app.plugin_view = None;
+                app.push_message(ChatMessage {
+                    role: MessageRole::System,
+                    text: "Error: Unable to send plugin command to agent.".to_string(),
+                });
+            } else {
+                app.status = ChatStatus::Thinking;
+                app.status_ticks = 0;
             }
         }
         _ => {}
@@ -1706,7 +2662,8 @@ fn parse_plugin_command(input: &str) -> Option<Result<PluginCommandRequest, Stri
     let tokens = trimmed.split_whitespace().collect::<Vec<_>>();
     if tokens.len() < 2 {
         return Some(Err(
-            "Usage: /plugin <add|remove|enable|disable|list|catalog> [arg]".to_string(),
+            "Usage: /plugin <add|validate|remove|enable|disable|env|list|catalog|reload|status> [arg]"
+                .to_string(),
         ));
     }
 
@@ -1728,6 +2685,19 @@ fn parse_plugin_command(input: &str) -> Option<Result<PluginCommandRequest, Stri
                 }
             }
         }
+        "validate" => {
+            let source = trimmed
+                .splitn(3, ' ')
+                .nth(2)
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            if source.is_empty() {
+                Err("Usage: /plugin validate <directory_path>".to_string())
+            } else {
+                Ok(PluginCommandRequest::Validate { source })
+            }
+        }
         "remove" => {
             if tokens.len() < 3 {
                 Err("Usage: /plugin remove <plugin_name>".to_string())
@@ -1755,14 +2725,346 @@ fn parse_plugin_command(input: &str) -> Option<Result<PluginCommandRequest, Stri
                 })
             }
         }
+        "env" => match tokens.get(2..).unwrap_or_default() {
+            [plugin_name, "unset", key] => Ok(PluginCommandRequest::SetEnv {
+                plugin_name: plugin_name.to_string(),
+                key: key.to_string(),
+                value: None,
+            }),
+            [plugin_name, assignment] if assignment.contains('=') => {
+                let (key, value) = assignment.split_once('=').unwrap_or_default();
+                Ok(PluginCommandRequest::SetEnv {
+                    plugin_name: plugin_name.to_string(),
+                    key: key.to_string(),
+                    value: Some(value.to_string()),
+                })
+            }
+            _ => Err(
+                "Usage: /plugin env <plugin_name> <KEY=VALUE> | /plugin env <plugin_name> unset <KEY>"
+                    .to_string(),
+            ),
+        },
         "list" => Ok(PluginCommandRequest::List),
         "catalog" => Ok(PluginCommandRequest::Catalog),
-        _ => Err("Usage: /plugin <add|remove|enable|disable|list|catalog> [arg]".to_string()),
+        "reload" => Ok(PluginCommandRequest::Reload),
+        "status" => Ok(PluginCommandRequest::Status),
+        _ => Err(
+            "Usage: /plugin <add|validate|remove|enable|disable|env|list|catalog|reload|status> [arg]"
+                .to_string(),
+        ),
     };
 
     Some(result)
 }
 
+fn parse_provider_command(input: &str) -> Option<Result<ChatCommand, String>> {
+    let trimmed = input.trim();
+    let rest = trimmed.strip_prefix("/provider")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let tokens = rest.split_whitespace().collect::<Vec<_>>();
+    match tokens.as_slice() {
+        ["unset", provider] => Some(Ok(ChatCommand::RemoveProviderKey {
+            provider: provider.to_string(),
+        })),
+        _ => Some(Err("Usage: /provider unset <provider_id>".to_string())),
+    }
+}
+
+fn parse_config_command(input: &str) -> Option<Result<ChatCommand, String>> {
+    let trimmed = input.trim();
+    let rest = trimmed.strip_prefix("/config")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let tokens = rest.split_whitespace().collect::<Vec<_>>();
+    let (verb, args) = match tokens.split_first() {
+        Some((verb, args)) => (*verb, args),
+        None => ("", &[][..]),
+    };
+    let (include_keys, path) = match args {
+        ["--keys", path] => (true, *path),
+        [path] => (false, *path),
+        _ => {
+            return Some(Err(
+                "Usage: /config <export|import> [--keys] <path>".to_string()
+            ));
+        }
+    };
+
+    match verb {
+        "export" => Some(Ok(ChatCommand::ExportConfig {
+            path: path.to_string(),
+            include_keys,
+        })),
+        "import" => Some(Ok(ChatCommand::ImportConfig {
+            path: path.to_string(),
+            include_keys,
+        })),
+        _ => Some(Err(
+            "Usage: /config <export|import> [--keys] <path>".to_string()
+        )),
+    }
+}
+
+fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86_400, seconds / 3_600 % 24, seconds / 60 % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m {}s", seconds % 60)
+    }
+}
+
+fn parse_agent_command(input: &str) -> Option<Result<ChatCommand, String>> {
+    let trimmed = input.trim();
+    let rest = trimmed.strip_prefix("/agent")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    match rest.trim() {
+        "status" => Some(Ok(ChatCommand::CheckReadiness)),
+        _ => Some(Err("Usage: /agent status".to_string())),
+    }
+}
+
+fn parse_pause_command(input: &str) -> Option<Result<ChatCommand, String>> {
+    match input.trim() {
+        "/pause" => Some(Ok(ChatCommand::SetPaused { paused: true })),
+        "/resume" => Some(Ok(ChatCommand::SetPaused { paused: false })),
+        "/step" => Some(Ok(ChatCommand::StepAgent)),
+        "/cancel" => Some(Ok(ChatCommand::CancelTurn)),
+        _ => None,
+    }
+}
+
+fn parse_model_command(input: &str) -> Option<Result<ChatCommand, String>> {
+    let trimmed = input.trim();
+    let rest = trimmed.strip_prefix("/model")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    match rest.trim() {
+        "check" => Some(Ok(ChatCommand::CheckModel)),
+        "history" => Some(Ok(ChatCommand::ModelHistory)),
+        _ => Some(Err("Usage: /model <check|history>".to_string())),
+    }
+}
+
+fn format_model_history(entries: &[ModelChange]) -> String {
+    if entries.is_empty() {
+        return "No model configuration changes recorded.".to_string();
+    }
+
+    let now = now_millis();
+    let mut lines = vec![format!(
+        "Model configuration history ({}, newest first):",
+        entries.len()
+    )];
+    for entry in entries {
+        lines.push(format!(
+            "- {} ago {}/{} ({})",
+            format_uptime(((now - entry.changed_at).max(0) / 1000) as u64),
+            entry.provider,
+            entry.model,
+            entry.source
+        ));
+    }
+    lines.join("\n")
+}
+
+fn parse_search_command(input: &str) -> Option<Result<ChatCommand, String>> {
+    let trimmed = input.trim();
+    let rest = trimmed.strip_prefix("/search")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let query = rest.trim();
+    if query.is_empty() {
+        return Some(Err("Usage: /search <query>".to_string()));
+    }
+
+    Some(Ok(ChatCommand::SearchChat {
+        query: query.to_string(),
+    }))
+}
+
+fn format_search_results(query: &str, hits: &[ChatSearchHit]) -> String {
+    if hits.is_empty() {
+        return format!("No chat messages match \"{query}\"");
+    }
+
+    let mut lines = vec![format!(
+        "Search results for \"{query}\" ({}, newest first):",
+        hits.len()
+    )];
+    for hit in hits {
+        let text = hit.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let preview = if text.chars().count() > 160 {
+            format!("{}...", text.chars().take(160).collect::<String>())
+        } else {
+            text
+        };
+        lines.push(format!(
+            "- [{}] {}: {preview}",
+            hit.role.as_deref().unwrap_or("unknown"),
+            hit.session_id
+        ));
+    }
+    lines.join("\n")
+}
+
+fn parse_replay_command(input: &str) -> Option<Result<ChatCommand, String>> {
+    let trimmed = input.trim();
+    let rest = trimmed.strip_prefix("/replay")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let path = rest.trim();
+    if path.is_empty() {
+        return Some(Err("Usage: /replay <file_path>".to_string()));
+    }
+
+    Some(Ok(ChatCommand::ReplayFile {
+        path: path.to_string(),
+    }))
+}
+
+fn parse_audit_command(input: &str) -> Option<Result<ChatCommand, String>> {
+    let trimmed = input.trim();
+    let rest = trimmed.strip_prefix("/audit")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let usage = "Usage: /audit [actuator] [--since <N>m|<N>h|<N>d]";
+    let mut actuator = None;
+    let mut since_secs = None;
+    let mut parts = rest.split_whitespace();
+    while let Some(part) = parts.next() {
+        if part == "--since" {
+            let Some(secs) = parts.next().and_then(parse_duration_secs) else {
+                return Some(Err(usage.to_string()));
+            };
+            since_secs = Some(secs);
+        } else if actuator.is_none() && !part.starts_with("--") {
+            actuator = Some(part.to_string());
+        } else {
+            return Some(Err(usage.to_string()));
+        }
+    }
+
+    Some(Ok(ChatCommand::QueryAudit {
+        actuator,
+        since_secs,
+    }))
+}
+
+fn parse_duration_secs(raw: &str) -> Option<u64> {
+    let unit = match raw.chars().last()? {
+        'm' => 60,
+        'h' => 3_600,
+        'd' => 86_400,
+        _ => return None,
+    };
+    let count = raw[..raw.len() - 1].parse::<u64>().ok()?;
+    count.checked_mul(unit)
+}
+
+fn format_audit_entries(entries: &[AuditEntry]) -> String {
+    if entries.is_empty() {
+        return "No audited actions match.".to_string();
+    }
+
+    let now = now_millis();
+    let mut lines = vec![format!(
+        "Audited actions ({}, newest first):",
+        entries.len()
+    )];
+    for entry in entries {
+        let status = serde_json::to_value(&entry.status)
+            .ok()
+            .and_then(|value| value.as_str().map(ToString::to_string))
+            .unwrap_or_default();
+        let mut line = format!(
+            "- {} ago {}/{} {status}",
+            format_uptime(((now - entry.created_at).max(0) / 1000) as u64),
+            entry.plugin,
+            entry.actuator
+        );
+        if let Some(reason) = entry
+            .deny_reason
+            .and_then(|reason| serde_json::to_value(reason).ok())
+            .and_then(|value| value.as_str().map(ToString::to_string))
+        {
+            line.push_str(&format!(" ({reason})"));
+        }
+        line.push_str(&format!(" [{} {}]", entry.session_id, entry.turn_id));
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+fn format_agent_error(code: ErrorCode, message: &str) -> String {
+    let hint = match code {
+        ErrorCode::SetupRequired => " Finish the setup flow first.",
+        ErrorCode::NotConfigured => " Check the agent's provider and plugin configuration.",
+        ErrorCode::SessionMismatch => " Reconnect with /agent connect <port>.",
+        ErrorCode::LimitExceeded => " Check the chat plugin's limits in its manifest.",
+        _ => "",
+    };
+    let code = serde_json::to_value(code)
+        .ok()
+        .and_then(|value| value.as_str().map(ToString::to_string))
+        .unwrap_or_default();
+    format!("{message} [{code}]{hint}")
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+fn parse_export_command(input: &str) -> Option<Result<ChatCommand, String>> {
+    let trimmed = input.trim();
+    let rest = trimmed.strip_prefix("/export")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let rest = rest.trim();
+    let (all_sessions, path) = match rest.strip_prefix("all ") {
+        Some(path) => (true, path.trim()),
+        None => (false, rest),
+    };
+    if path.is_empty() {
+        return Some(Err("Usage: /export [all] <path.jsonl|path.csv>".to_string()));
+    }
+
+    let format = if path.to_lowercase().ends_with(".csv") {
+        ExportFormat::Csv
+    } else {
+        ExportFormat::Jsonl
+    };
+
+    Some(Ok(ChatCommand::ExportEvents {
+        all_sessions,
+        format,
+        path: path.to_string(),
+    }))
+}
+
 fn draw_chat(frame: &mut Frame, app: &mut ChatApp) {
     let area = frame.area();
     if should_render_sidenav(area) {
@@ -1780,7 +3082,7 @@ fn draw_chat(frame: &mut Frame, app: &mut ChatApp) {
             app.agent_port,
             app.agent_workspace.as_deref(),
             app.project_workspace.as_deref(),
-            &app.planned_actions,
+            build_planning_text(&app.planned_actions, app.last_turn_timing.as_ref()),
         );
     } else {
         draw_chat_panel(frame, area, app);
@@ -1824,37 +3126,12 @@ fn draw_chat_panel(frame: &mut Frame, area: Rect, app: &mut ChatApp) {
         height: rows[0].height,
     };
 
-    let history_lines = build_history_lines(&app.messages, history_area.width as usize);
-
-    let content_length = history_lines.len().max(1);
-    let viewport_height = history_area.height as usize;
-    let max_scroll = content_length.saturating_sub(viewport_height);
-    app.history_max_scroll = max_scroll;
-
-    if app.follow_tail {
-        app.scroll_offset = max_scroll;
+    if let Some(view) = app.plugin_view.as_ref() {
+        draw_plugin_view(frame, history_area, view);
     } else {
-        app.scroll_offset = app.scroll_offset.min(max_scroll);
+        draw_history(frame, history_area, app);
     }
 
-    let history = Paragraph::new(Text::from(history_lines))
-        .style(
-            Style::default()
-                .bg(Color::Rgb(28, 35, 45))
-                .fg(Color::Rgb(219, 227, 238)),
-        )
-        .scroll((app.scroll_offset as u16, 0))
-        .wrap(Wrap { trim: false });
-    frame.render_widget(history, history_area);
-
-    let mut scrollbar_state = ScrollbarState::new(content_length)
-        .position(app.scroll_offset)
-        .viewport_content_length(viewport_height);
-    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
-        .thumb_style(Style::default().fg(Color::Rgb(140, 170, 200)))
-        .track_style(Style::default().fg(Color::Rgb(70, 80, 92)));
-    frame.render_stateful_widget(scrollbar, history_area, &mut scrollbar_state);
-
     let cursor = if app.cursor_visible { "█" } else { " " };
 
     let input_label_outer = Block::default().style(
@@ -2013,6 +3290,132 @@ fn draw_chat_panel(frame: &mut Frame, area: Rect, app: &mut ChatApp) {
     frame.render_widget(status, rows[3]);
 }
 
+fn draw_history(frame: &mut Frame, area: Rect, app: &mut ChatApp) {
+    let history_lines = build_history_lines(&app.messages, area.width as usize);
+
+    let content_length = history_lines.len().max(1);
+    let viewport_height = area.height as usize;
+    let max_scroll = content_length.saturating_sub(viewport_height);
+    app.history_max_scroll = max_scroll;
+
+    if app.follow_tail {
+        app.scroll_offset = max_scroll;
+    } else {
+        app.scroll_offset = app.scroll_offset.min(max_scroll);
+    }
+
+    let history = Paragraph::new(Text::from(history_lines))
+        .style(
+            Style::default()
+                .bg(Color::Rgb(28, 35, 45))
+                .fg(Color::Rgb(219, 227, 238)),
+        )
+        .scroll((app.scroll_offset as u16, 0))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(history, area);
+
+    let mut scrollbar_state = ScrollbarState::new(content_length)
+        .position(app.scroll_offset)
+        .viewport_content_length(viewport_height);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .thumb_style(Style::default().fg(Color::Rgb(140, 170, 200)))
+        .track_style(Style::default().fg(Color::Rgb(70, 80, 92)));
+    frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
+}
+
+fn draw_plugin_view(frame: &mut Frame, area: Rect, view: &PluginView) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(area);
+
+    let header = Paragraph::new(Line::from(vec![
+        Span::styled(
+            "Plugins",
+            Style::default()
+                .fg(Color::Rgb(255, 240, 140))
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            "  Up/Down to choose, Enter to toggle, Esc to close",
+            Style::default().fg(Color::Rgb(150, 160, 174)),
+        ),
+    ]))
+    .style(Style::default().bg(Color::Rgb(28, 35, 45)));
+    frame.render_widget(header, rows[0]);
+
+    if view.plugins.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No plugins available")
+                .style(Style::default().fg(Color::Rgb(150, 160, 174))),
+            rows[1],
+        );
+        return;
+    }
+
+    let items = view
+        .plugins
+        .iter()
+        .map(|plugin| {
+            let (toggle, toggle_color) = if plugin.enabled {
+                ("[on] ", Color::Rgb(124, 201, 146))
+            } else {
+                ("[off]", Color::Rgb(214, 116, 116))
+            };
+            let status_color = if plugin.status_message == "ready" {
+                Color::Rgb(124, 201, 146)
+            } else {
+                Color::Rgb(232, 190, 110)
+            };
+            let components = format!(
+                "      actuators: {}  sensors: {}",
+                join_or_none(&plugin.actuators),
+                join_or_none(&plugin.sensors)
+            );
+
+            ListItem::new(Text::from(vec![
+                Line::from(vec![
+                    Span::styled(toggle, Style::default().fg(toggle_color)),
+                    Span::raw(format!(
+                        " {} v{} ({})",
+                        plugin.name, plugin.version, plugin.source
+                    )),
+                ]),
+                Line::from(Span::styled(
+                    format!("      {}", plugin.status_message),
+                    Style::default().fg(status_color),
+                )),
+                Line::from(Span::styled(
+                    components,
+                    Style::default().fg(Color::Rgb(144, 163, 183)),
+                )),
+            ]))
+        })
+        .collect::<Vec<_>>();
+
+    let list = List::new(items)
+        .style(
+            Style::default()
+                .bg(Color::Rgb(28, 35, 45))
+                .fg(Color::Rgb(219, 227, 238)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::Rgb(56, 74, 96))
+                .fg(Color::Rgb(243, 248, 255)),
+        )
+        .highlight_symbol("> ");
+    let mut state = ListState::default().with_selected(Some(view.selected));
+    frame.render_stateful_widget(list, rows[1], &mut state);
+}
+
+fn join_or_none(values: &[String]) -> String {
+    if values.is_empty() {
+        return "none".to_string();
+    }
+    values.join(", ")
+}
+
 fn build_history_lines(messages: &[ChatMessage], width: usize) -> Vec<Line<'static>> {
     if width == 0 {
         return vec![Line::from("")];
@@ -2481,7 +3884,7 @@ fn draw_sidenav(
     agent_port: u16,
     agent_workspace: Option<&str>,
     project_workspace: Option<&str>,
-    planned_actions: &[PlannedAction],
+    planning_text: Text<'static>,
 ) {
     let sidenav_bg = Color::Rgb(16, 19, 25);
     frame.render_widget(
@@ -2524,7 +3927,7 @@ fn draw_sidenav(
             height: todos_height,
         };
 
-        let todos_content = build_planning_text(planned_actions);
+        let todos_content = planning_text;
 
         let todos_container = Paragraph::new(todos_content)
             .block(
@@ -2617,7 +4020,10 @@ fn draw_sidenav(
     }
 }
 
-fn build_planning_text(planned_actions: &[PlannedAction]) -> Text<'static> {
+fn build_planning_text(
+    planned_actions: &[PlannedAction],
+    last_turn_timing: Option<&TurnTiming>,
+) -> Text<'static> {
     let mut lines = vec![
         Line::from(Span::styled(
             "Task Planning",
@@ -2626,6 +4032,14 @@ fn build_planning_text(planned_actions: &[PlannedAction]) -> Text<'static> {
         Line::from(""),
     ];
 
+    if let Some(timing) = last_turn_timing {
+        lines.push(Line::from(Span::styled(
+            format_turn_timing(timing),
+            Style::default().fg(Color::Rgb(144, 163, 183)),
+        )));
+        lines.push(Line::from(""));
+    }
+
     if planned_actions.is_empty() {
         lines.push(Line::from(Span::styled(
             "No planning to show yet...",
@@ -2664,6 +4078,25 @@ fn build_planning_text(planned_actions: &[PlannedAction]) -> Text<'static> {
     Text::from(lines)
 }
 
+fn format_turn_timing(timing: &TurnTiming) -> String {
+    let action_total = timing.action_ms.iter().sum::<u64>();
+    let model = timing.model_ms.unwrap_or(0);
+    let total = timing.plan_ms + action_total + model;
+    let slowest = [
+        ("plan", timing.plan_ms),
+        ("actions", action_total),
+        ("model", model),
+    ]
+    .into_iter()
+    .max_by_key(|(_, ms)| *ms)
+    .map(|(phase, _)| phase)
+    .unwrap_or("plan");
+    format!(
+        "Last turn {total} ms (plan {} ms, actions {action_total} ms, model {model} ms; slowest: {slowest})",
+        timing.plan_ms
+    )
+}
+
 fn summarize_action_args(action: &PlannedAction) -> Option<String> {
     if action.actuator == "filesystem_read" {
         return action
@@ -2743,3 +4176,109 @@ fn format_workspace_path(path: Option<&str>) -> String {
 
     normalized_path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trim_to_limit_drops_oldest_entries() {
+        let mut entries = vec![1, 2, 3, 4, 5];
+        trim_to_limit(&mut entries, 3);
+        assert_eq!(entries, vec![3, 4, 5]);
+
+        trim_to_limit(&mut entries, 10);
+        assert_eq!(entries, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn history_limit_from_env_falls_back_on_bad_values() {
+        let var_name = "LOOPER_TEST_HISTORY_LIMIT";
+        for (raw, expected) in [
+            ("25", 25),
+            (" 7 ", 7),
+            ("0", 100),
+            ("-3", 100),
+            ("many", 100),
+        ] {
+            unsafe { env::set_var(var_name, raw) };
+            assert_eq!(history_limit_from_env(var_name, 100), expected, "{raw:?}");
+        }
+        unsafe { env::remove_var(var_name) };
+        assert_eq!(history_limit_from_env(var_name, 100), 100);
+    }
+
+    fn setup_app() -> SetupApp {
+        SetupApp {
+            stage: SetupStage::Workspace,
+            should_quit: false,
+            workspace_input: String::new(),
+            provider_index: 0,
+            model_input: default_model_for_provider(PROVIDERS[0]).to_string(),
+            api_key_input: String::new(),
+            confirm_index: 0,
+            error_message: None,
+            cursor_visible: true,
+            agent_port: 0,
+        }
+    }
+
+    fn round_trip(app: &SetupApp) -> SetupApp {
+        let text = serde_json::to_string(&setup_draft(app)).unwrap();
+        let mut restored = setup_app();
+        apply_setup_draft(&mut restored, serde_json::from_str(&text).unwrap());
+        restored
+    }
+
+    #[test]
+    fn setup_draft_round_trips_through_apply() {
+        let workspace = env::temp_dir().to_string_lossy().to_string();
+        let mut app = setup_app();
+        app.stage = SetupStage::ApiKey;
+        app.workspace_input = format!(" {workspace} ");
+        app.provider_index = 1;
+        app.model_input = "custom-model".to_string();
+        app.api_key_input = "sk-not-saved".to_string();
+
+        let restored = round_trip(&app);
+        assert!(restored.stage == SetupStage::ApiKey);
+        assert_eq!(restored.workspace_input, workspace);
+        assert_eq!(restored.provider_index, 1);
+        assert_eq!(restored.model_input, "custom-model");
+        assert!(restored.api_key_input.is_empty());
+        assert!(
+            !serde_json::to_string(&setup_draft(&app))
+                .unwrap()
+                .contains("sk-not-saved")
+        );
+
+        app.stage = SetupStage::Model;
+        assert!(round_trip(&app).stage == SetupStage::Model);
+    }
+
+    #[test]
+    fn stale_setup_draft_falls_back_to_earlier_stage() {
+        let workspace = env::temp_dir().to_string_lossy().to_string();
+        let draft = |workspace_dir: &str, provider: &str, model: &str| SetupDraft {
+            stage: SetupStage::Confirm,
+            workspace_dir: workspace_dir.to_string(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+        };
+
+        let mut app = setup_app();
+        apply_setup_draft(&mut app, draft("/no/such/looper/workspace", "openai", "m"));
+        assert!(app.stage == SetupStage::Workspace);
+        assert_eq!(app.workspace_input, "/no/such/looper/workspace");
+
+        let mut app = setup_app();
+        apply_setup_draft(&mut app, draft(&workspace, "retired-provider", "m"));
+        assert!(app.stage == SetupStage::Provider);
+
+        let mut app = setup_app();
+        apply_setup_draft(&mut app, draft(&workspace, PROVIDERS[2], " "));
+        assert!(app.stage == SetupStage::Model);
+        assert_eq!(app.provider_index, 2);
+        assert_eq!(app.model_input, default_model_for_provider(PROVIDERS[2]));
+    }
+}